@@ -14,7 +14,132 @@ use super::visitor::{walk_term, Visitor};
 
 enum RuleParamMatch {
     True,
+    /// A single parameter (by 1-based index) failed to match, carrying the failure message.
     False(String),
+    /// Every parameter that failed to match a rule type, paired with its 1-based index, so
+    /// callers can report all offending parameters instead of only the first.
+    Failures(Vec<(usize, String)>),
+}
+
+/// A structured, programmatically-inspectable reason a rule parameter failed to match a rule
+/// type template. This is the opt-in counterpart to `RuleParamMatch`'s plain-text messages --
+/// callers that want to render their own diagnostics (e.g. an editor) can match on these variants
+/// instead of parsing strings. 1-based `param_index`es match `RuleParamMatch::Failures`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchFailureReason {
+    /// The rule and rule type have a different number of parameters.
+    ArityMismatch { expected: usize, got: usize },
+    /// The parameter's specializer names a type that isn't the rule type's expected class (and
+    /// isn't a dict/list/value mismatch more specifically captured by another variant).
+    SpecializerTypeMismatch {
+        param_index: usize,
+        expected_type: String,
+        actual_value: String,
+    },
+    /// The parameter's dictionary specializer is missing a field the rule type requires.
+    MissingDictField { param_index: usize, field: String },
+    /// The parameter's instance specializer's class is not `class`-or-a-subclass of the required
+    /// rule type class, per the registered MRO.
+    NotASubclass {
+        param_index: usize,
+        class: String,
+        required: String,
+    },
+    /// A mismatch that doesn't fit one of the more specific variants above.
+    Other { param_index: usize, message: String },
+}
+
+/// Variable bindings accumulated while recursively unifying nested field-level specializers.
+type FieldBindings = HashMap<Symbol, Term>;
+
+/// A single argument pattern in an SSR (structural search & replace) template.
+#[derive(Clone, Debug)]
+enum SsrTerm {
+    /// A `$name` token: binds to any concrete term in that position. An optional `: Type`
+    /// annotation (e.g. `$resource: Document`) additionally requires the matched rule parameter's
+    /// specializer to be that class or an MRO-registered subclass of it.
+    Metavar(String, Option<Symbol>),
+    /// A bare identifier: matches only a rule parameter that is exactly that variable.
+    Var(Symbol),
+    /// A literal (string/integer/boolean): matches only an equal, unspecialized value parameter.
+    Value(Term),
+}
+
+/// A parsed SSR template: a rule name plus its flat list of head-parameter patterns.
+struct SsrTemplate {
+    name: Symbol,
+    params: Vec<SsrTerm>,
+}
+
+/// A source file, identified by the `src_id` it was registered under via `add_source`, to scope
+/// validation to via `validate_rules_in_ranges`.
+///
+/// `start`/`end` are accepted for forward compatibility with sub-file (byte-offset) scoping, but
+/// this snapshot has no `Term` byte-offset accessor to filter rules on, so only `source_id` is
+/// currently used -- a range scopes validation to its whole file rather than just the touched
+/// region within it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceRange {
+    pub source_id: u64,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Partitions the variables appearing in `x = y` (`Operator::Unify` between two `Variable`s)
+/// conjuncts into equivalence classes, via a union-find over the conjunct list, and returns each
+/// variable mapped to its class's canonical representative (the lexicographically least symbol
+/// name in the class). Pure syntactic grouping of bindings already present in the term tree; it
+/// does not consult or require live VM bindings, so it's usable wherever a flat list of
+/// conjuncts is available (e.g. scoped down from a partial query's constraints before handing
+/// them to the VM-bound half of constraint extraction -- see the TODO on `build_filter_plan`).
+pub fn partition_equivs(conjuncts: &[Term]) -> HashMap<Symbol, Symbol> {
+    let mut parent: HashMap<Symbol, Symbol> = HashMap::new();
+
+    fn find(parent: &mut HashMap<Symbol, Symbol>, var: &Symbol) -> Symbol {
+        let next = parent.get(var).cloned().unwrap_or_else(|| var.clone());
+        if &next == var {
+            var.clone()
+        } else {
+            let root = find(parent, &next);
+            parent.insert(var.clone(), root.clone());
+            root
+        }
+    }
+
+    for conjunct in conjuncts {
+        if let Value::Expression(Operation {
+            operator: Operator::Unify,
+            args,
+        }) = conjunct.value()
+        {
+            if let [left, right] = args.as_slice() {
+                if let (Value::Variable(l), Value::Variable(r)) = (left.value(), right.value()) {
+                    parent.entry(l.clone()).or_insert_with(|| l.clone());
+                    parent.entry(r.clone()).or_insert_with(|| r.clone());
+                    let (root_l, root_r) = (find(&mut parent, l), find(&mut parent, r));
+                    if root_l != root_r {
+                        let (keep, merge) = if root_l.0 <= root_r.0 {
+                            (root_l, root_r)
+                        } else {
+                            (root_r, root_l)
+                        };
+                        parent.insert(merge, keep);
+                    }
+                }
+            }
+        }
+    }
+
+    parent
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>()
+        .into_iter()
+        .map(|v| {
+            let root = find(&mut parent, &v);
+            (v, root)
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -38,7 +163,15 @@ pub struct KnowledgeBase {
     loaded_content: HashMap<String, String>,
 
     rules: HashMap<Symbol, GenericRule>,
+    /// Provenance: maps a source's `src_id` to the names of the `GenericRule`s that have at least
+    /// one `Rule` originating from it, so `remove_source` can find and drop just those rules when
+    /// a file is reloaded instead of clearing the whole knowledge base.
+    rules_by_source: HashMap<u64, Vec<Symbol>>,
     rule_types: RuleTypes,
+    /// Map from alias name to the canonical registered class name it stands in for, e.g. so a
+    /// class renamed across a host-library upgrade (`OldRepo -> Repo`) can still be referenced by
+    /// its old name in specializers without rewriting every policy.
+    aliases: HashMap<Symbol, Symbol>,
     pub sources: Sources,
     /// For symbols returned from gensym.
     gensym_counter: Counter,
@@ -48,6 +181,40 @@ pub struct KnowledgeBase {
 
     /// Resource block bookkeeping.
     pub resource_blocks: ResourceBlocks,
+
+    /// Memoized transitive membership (concrete, non-union symbols only) per union symbol, used
+    /// when checking whether one union specializer is a subtype of another.
+    union_membership_cache: std::cell::RefCell<HashMap<Symbol, HashSet<Symbol>>>,
+
+    /// "Did you mean `X`?" hints computed the last time `validate_rule_types_scoped` ran a
+    /// full (unscoped) pass, keyed by the missing required rule's name. Populated while
+    /// `self.rules` still holds every rule from the load that produced the corresponding
+    /// `MissingRequiredRule` diagnostic, so it survives even after `clear_rules` empties `rules`
+    /// in response to that same diagnostic -- letting `Polar::diagnostic_load_json` look the
+    /// suggestion up afterward without racing the clear. Deliberately left untouched by
+    /// `clear_rules`: a stale entry for a name that isn't currently missing is never looked up.
+    missing_rule_suggestions: std::cell::RefCell<HashMap<Symbol, String>>,
+
+    /// Secondary ("related") source location for a multi-span warning, keyed by the *flagged*
+    /// rule/template's own rendered Polar text -- the only stable handle available once the
+    /// diagnostic has been built, since `Diagnostic`/`ValidationError` (in `diagnostic.rs`/
+    /// `error.rs`) carry one primary location per diagnostic and don't expose a `related` field of
+    /// their own to populate. Populated by `check_redundant_rules`/`check_redundant_rule_types` at
+    /// diagnostic-construction time, while the earlier rule/template that shadows the flagged one
+    /// is still in scope; read back by `Polar::diagnostic_load_json` to attach a second span to the
+    /// JSON diagnostic.
+    ///
+    /// Rendered text alone isn't a unique key -- two independent rules (possibly from two
+    /// different files) can render to the same text, e.g. two files that each declare `f(1);
+    /// f(1);`. Each occurrence's source ID is queued (FIFO) under its shared text key instead of
+    /// overwriting a single slot, and `related_diagnostic_source_file` dequeues one entry per
+    /// lookup. This relies on `check_redundant_rules`/`check_redundant_rule_types` populating the
+    /// queues in the same relative order that `diagnostic_load_json` later drains them in (both
+    /// walk the same diagnostics list once, in order), which holds for `diagnostic_load`'s
+    /// from-scratch KB -- it isn't a general-purpose cache safe to read from arbitrary code.
+    /// Deliberately left untouched by `clear_rules`, same as `missing_rule_suggestions`, since a
+    /// stale entry for text that isn't currently flagged is never looked up.
+    related_diagnostic_sources: std::cell::RefCell<HashMap<String, std::collections::VecDeque<u64>>>,
 }
 
 impl KnowledgeBase {
@@ -88,6 +255,13 @@ impl KnowledgeBase {
     }
 
     pub fn add_rule(&mut self, rule: Rule) {
+        let rule = self.flatten_nested_patterns(rule);
+        if let Some(src_id) = rule.body.get_source_id() {
+            self.rules_by_source
+                .entry(src_id)
+                .or_default()
+                .push(rule.name.clone());
+        }
         let generic_rule = self
             .rules
             .entry(rule.name.clone())
@@ -95,6 +269,91 @@ impl KnowledgeBase {
         generic_rule.add_rule(Arc::new(rule));
     }
 
+    /// Rewrite `rule` so that no parameter specializer has nesting depth greater than 1.
+    ///
+    /// Whenever a specializer's field value is itself a nested `InstanceLiteral`/`Dictionary`
+    /// pattern, hoist it out: allocate a fresh variable via `gensym`, replace the nested pattern
+    /// with that variable in the head, and splice an `Isa` conjunct (`fresh_var matches
+    /// <extracted pattern>`) onto the rule body, mirroring how specializers are already lowered
+    /// to `Isa` checks elsewhere. This is applied once, at `add_rule` time, so `check_param` and
+    /// the MRO/union matching it drives only ever need to reason about one-level patterns.
+    fn flatten_nested_patterns(&self, mut rule: Rule) -> Rule {
+        let mut extra_conjuncts = vec![];
+        for param in rule.params.iter_mut() {
+            if let Some(specializer) = &param.specializer {
+                if let Value::Pattern(pattern) = specializer.value() {
+                    if let Some(flattened) = self.flatten_pattern(pattern, &mut extra_conjuncts) {
+                        param.specializer = Some(term!(Value::Pattern(flattened)));
+                    }
+                }
+            }
+        }
+        for conjunct in extra_conjuncts {
+            rule.body = Self::splice_conjunct(rule.body, conjunct);
+        }
+        rule
+    }
+
+    /// Hoist any field of `pattern` whose value is itself a nested pattern out into a fresh
+    /// variable, pushing an `Isa` conjunct for each hoisted field onto `extra_conjuncts`. Returns
+    /// `None` if `pattern` was already depth-1 (nothing to hoist).
+    fn flatten_pattern(&self, pattern: &Pattern, extra_conjuncts: &mut Vec<Term>) -> Option<Pattern> {
+        let fields = match pattern {
+            Pattern::Instance(InstanceLiteral { fields, .. }) => fields,
+            Pattern::Dictionary(fields) => fields,
+            // Logical combinators carry no fields of their own to hoist; nested patterns inside
+            // their operands are left alone since they aren't reachable via `check_param`.
+            Pattern::Logical(..) => return None,
+        };
+        let mut changed = false;
+        let mut new_fields = fields.fields.clone();
+        for (key, value) in fields.fields.iter() {
+            if let Value::Pattern(nested) = value.value() {
+                changed = true;
+                let fresh_var = self.gensym("flattened");
+                let fresh_term = term!(fresh_var);
+                extra_conjuncts.push(term!(Value::Expression(Operation {
+                    operator: Operator::Isa,
+                    args: vec![fresh_term.clone(), term!(Value::Pattern(nested.clone()))],
+                })));
+                new_fields.insert(key.clone(), fresh_term);
+            }
+        }
+        if !changed {
+            return None;
+        }
+        let new_fields = Dictionary { fields: new_fields };
+        Some(match pattern {
+            Pattern::Instance(InstanceLiteral { tag, .. }) => Pattern::Instance(InstanceLiteral {
+                tag: tag.clone(),
+                fields: new_fields,
+            }),
+            Pattern::Dictionary(_) => Pattern::Dictionary(new_fields),
+        })
+    }
+
+    /// Splice `conjunct` onto `body`, flattening into an existing top-level `And` rather than
+    /// nesting a new one.
+    fn splice_conjunct(body: Term, conjunct: Term) -> Term {
+        match body.value() {
+            Value::Expression(Operation {
+                operator: Operator::And,
+                args,
+            }) => {
+                let mut args = args.clone();
+                args.push(conjunct);
+                term!(Value::Expression(Operation {
+                    operator: Operator::And,
+                    args,
+                }))
+            }
+            _ => term!(Value::Expression(Operation {
+                operator: Operator::And,
+                args: vec![body, conjunct],
+            })),
+        }
+    }
+
     pub fn validate_rules(&self) -> Vec<Diagnostic> {
         // Prior to #1310 these validations were not order dependent due to the
         // use of static default rule types.
@@ -104,12 +363,163 @@ impl KnowledgeBase {
         // errors
         let mut diagnostics = vec![];
 
-        if let Err(e) = self.validate_rule_types() {
-            diagnostics.push(Diagnostic::Error(e));
+        match self.validate_rule_types() {
+            Ok(mut rule_type_diagnostics) => diagnostics.append(&mut rule_type_diagnostics),
+            Err(e) => diagnostics.push(Diagnostic::Error(e)),
         }
 
         diagnostics.append(&mut self.validate_rule_calls());
+        diagnostics.append(&mut self.check_redundant_rules());
+        diagnostics.append(&mut self.check_redundant_rule_types());
+        diagnostics.append(&mut self.validate_resource_block_schema());
+        diagnostics.append(&mut self.validate_registered_resource_types());
+
+        diagnostics
+    }
+
+    /// Walk every `resource`/`actor` block declaration and confirm the host actually registered
+    /// a constant (`register_constant`) and an MRO (`register_mro`/`add_mro`) for it. A
+    /// resource/actor block whose type the host never registered at all would otherwise only
+    /// fail later, as an opaque `UnregisteredClass` lookup failure the first time a rule actually
+    /// needed that type's MRO -- this reports it at load time instead, with the resource block's
+    /// own declaration as the error's span, so integrators get a clear signal about which
+    /// host-side bindings a policy expects.
+    fn validate_registered_resource_types(&self) -> Vec<Diagnostic> {
+        self.resource_blocks
+            .actors
+            .iter()
+            .chain(self.resource_blocks.resources.iter())
+            .filter_map(|term| {
+                let name = term.value().as_symbol().ok()?;
+                let missing = if !self.is_constant(name) {
+                    Some("constant")
+                } else if !self.mro.contains_key(self.resolve_alias(name)) {
+                    Some("MRO")
+                } else {
+                    None
+                };
+                missing.map(|missing| {
+                    Diagnostic::Error(self.set_error_context(
+                        term,
+                        error::ValidationError::UnregisteredResourceType {
+                            name: name.0.clone(),
+                            missing: missing.to_string(),
+                        },
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// Cedar-style schema check: treat the policy's `actor`/`resource` blocks as a declared
+    /// schema and verify (1) every resource-block relation's target names a declared
+    /// resource/actor type, and (2) the actor position of every `allow`/`has_permission` rule
+    /// specializes on a declared actor type (or the `Actor` union itself). Skipped entirely when
+    /// the policy declares no resource blocks at all, since there's no schema to check against.
+    ///
+    /// This deliberately stops short of the harder half of this check: verifying that a
+    /// shorthand rule's role/permission operand (the `"owner"` in `"write" if "owner" on
+    /// "repo";`) is actually declared on the relation's target resource block. `ResourceBlocks`
+    /// only exposes relation traversal (`shorthand_rules`, `relation_tuples`), not each resource's
+    /// own declared role/permission name set, so there's nothing here to check that against --
+    /// `error::ValidationError::UndeclaredRoleOrPermission` exists as the diagnostic this check
+    /// would raise once that data is exposed (see the `#[ignore]`d
+    /// `test_undeclared_role_or_permission_on_relation_target` in `integration_tests.rs`), but
+    /// nothing constructs it yet.
+    fn validate_resource_block_schema(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+
+        if self.resource_blocks.actors.is_empty() && self.resource_blocks.resources.is_empty() {
+            return diagnostics;
+        }
+
+        for (subject, name, _object) in self.resource_blocks.relation_tuples() {
+            if !self.is_declared_resource_or_actor(subject) {
+                diagnostics.push(Diagnostic::Error(self.set_error_context(
+                    name,
+                    error::ValidationError::UnknownRelationTarget {
+                        relation: name.to_polar(),
+                        target: subject.to_polar(),
+                    },
+                )));
+            }
+        }
+
+        for rule_name in [sym!("allow"), sym!("has_permission")] {
+            if let Some(generic_rule) = self.rules.get(&rule_name) {
+                for rule in generic_rule.rules.values() {
+                    if let Some(actor_param) = rule.params.first() {
+                        if let Some(Value::Pattern(Pattern::Instance(InstanceLiteral {
+                            tag,
+                            ..
+                        }))) = actor_param.specializer.as_ref().map(Term::value)
+                        {
+                            if tag.0.as_str() != ACTOR_UNION_NAME && !self.is_declared_actor(tag) {
+                                diagnostics.push(Diagnostic::Error(self.set_error_context(
+                                    &rule.body,
+                                    error::ValidationError::UndeclaredActorType {
+                                        rule: rule_name.0.clone(),
+                                        actor_type: tag.0.clone(),
+                                    },
+                                )));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        diagnostics
+    }
+
+    /// True if `ty` names a symbol that a `resource`/`actor` block declared somewhere in this KB.
+    /// Non-symbol terms (e.g. an already-invalid relation target) are treated as "nothing to
+    /// check" rather than flagged, since that's a different failure this pass doesn't own.
+    fn is_declared_resource_or_actor(&self, ty: &Term) -> bool {
+        match ty.value().as_symbol() {
+            Ok(sym) => self
+                .resource_blocks
+                .actors
+                .iter()
+                .chain(self.resource_blocks.resources.iter())
+                .filter_map(|term| term.value().as_symbol().ok())
+                .any(|declared| declared == sym),
+            Err(_) => true,
+        }
+    }
+
+    /// True if `name` was declared with an `actor` block somewhere in this KB.
+    fn is_declared_actor(&self, name: &Symbol) -> bool {
+        self.resource_blocks
+            .actors
+            .iter()
+            .filter_map(|term| term.value().as_symbol().ok())
+            .any(|declared| declared == name)
+    }
+
+    /// Like `validate_rules`, but when `ranges` is non-empty, only rule-type matching for rules
+    /// belonging to one of the given `SourceRange`s' files runs -- the rest of the KB is left
+    /// unchecked. Retains full-KB behavior when `ranges` is empty. Makes incremental validation
+    /// (e.g. an editor re-checking just the file a user is editing) cost proportional to the
+    /// edited file rather than the whole policy.
+    pub fn validate_rules_in_ranges(&self, ranges: &[SourceRange]) -> Vec<Diagnostic> {
+        if ranges.is_empty() {
+            return self.validate_rules();
+        }
 
+        let source_ids: HashSet<u64> = ranges.iter().map(|r| r.source_id).collect();
+        let names: HashSet<Symbol> = self
+            .rules_by_source
+            .iter()
+            .filter(|(src_id, _)| source_ids.contains(src_id))
+            .flat_map(|(_, names)| names.iter().cloned())
+            .collect();
+
+        let mut diagnostics = vec![];
+        match self.validate_rule_types_scoped(Some(&names)) {
+            Ok(mut rule_type_diagnostics) => diagnostics.append(&mut rule_type_diagnostics),
+            Err(e) => diagnostics.push(Diagnostic::Error(e)),
+        }
         diagnostics
     }
 
@@ -117,10 +527,165 @@ impl KnowledgeBase {
         check_undefined_rule_calls(self)
     }
 
+    // `RedundantRule`/`RedundantRuleType`'s `shadowed_by` field can only reference the other
+    // rule/template by its rendered Polar text, not a structural secondary span, because
+    // `Diagnostic`/`ValidationError` (in `diagnostic.rs`/`error.rs`) carry one primary location per
+    // diagnostic and don't expose a `related` field of their own -- those types live outside this
+    // source snapshot, so they can't be extended from here. What *is* reachable from this file is
+    // recording each shadowing rule/template's source file at diagnostic-construction time (see
+    // `related_diagnostic_sources`/`cache_related_diagnostic_source`) and letting
+    // `Polar::diagnostic_load_json` attach it as a second, file-level label alongside the primary
+    // one -- `check_ambiguous_precedence` and `check_singletons`, the other two checks a related
+    // span would help, don't exist in this file at all, so there's nothing here to wire them to.
+    //
+    /// Flag rules that are verbatim duplicates of an earlier rule with the same name -- i.e. the
+    /// later rule's parameters (including any non-specializer value patterns like the `1` in
+    /// `f(1)`, not just specializer types) and body all render identically to an earlier rule's.
+    ///
+    /// This is deliberately narrower than head subsumption. Polar evaluates *every* matching
+    /// clause via backtracking rather than committing to the first match -- `f(1); f(1);` yields
+    /// two solutions, not one, and in general a later rule whose head is subsumed by an earlier
+    /// one still fires and can still contribute a distinct body/result. So subsumption of heads
+    /// alone never makes a clause unreachable the way a dead `match` arm is, and flagging it would
+    /// spam warnings on the ordinary idiom of several same-head rules with different bodies.
+    /// What's left that's soundly "redundant" is a rule that is exactly the same rule as one
+    /// already loaded: it can't add a solution the earlier rule doesn't already produce.
+    fn check_redundant_rules(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        for generic_rule in self.rules.values() {
+            let mut seen: Vec<&Arc<Rule>> = vec![];
+            for rule in generic_rule.rules.values() {
+                if let Some(shadowed_by) = seen
+                    .iter()
+                    .find(|earlier| self.rule_is_duplicate(earlier, rule))
+                {
+                    let rule_text = rule.to_polar();
+                    self.cache_related_diagnostic_source(&rule_text, &shadowed_by.body);
+                    diagnostics.push(Diagnostic::Warning(self.set_error_context(
+                        &rule.body,
+                        error::ValidationError::RedundantRule {
+                            rule: rule_text,
+                            shadowed_by: shadowed_by.to_polar(),
+                        },
+                    )));
+                }
+                seen.push(rule);
+            }
+        }
+        diagnostics
+    }
+
+    /// True if `later` is a verbatim duplicate of `earlier`: same arity and identical rendered
+    /// Polar source for both the parameter list (specializers *and* plain value patterns) and the
+    /// body. Comparing rendered text rather than structural fields keeps this in sync with
+    /// whatever `Parameter`/`Term` equality `to_polar()` already accounts for, the same way
+    /// `RedundantRule`'s own fields are populated via `to_polar()` rather than a custom `PartialEq`.
+    fn rule_is_duplicate(&self, earlier: &Rule, later: &Rule) -> bool {
+        earlier.params.len() == later.params.len() && earlier.to_polar() == later.to_polar()
+    }
+
+    /// Flag any rule-type template that is strictly more general than another template declared
+    /// for the same rule name -- i.e. every rule that would match the more specific template also
+    /// matches the more general one, making the more specific template dead weight. Reuses
+    /// `rule_params_match`'s specificity relation (MRO, dict-field supersets, list-prefix rules)
+    /// by treating the more specific template as if it were a candidate rule being checked
+    /// against the more general one as a rule type.
+    ///
+    /// Checks both orderings of each unordered pair of templates -- subsumption doesn't care
+    /// which one was declared first, so a policy that declares the specific template (`Orange`)
+    /// before the general one (`Fruit`) must be flagged exactly the same as the reverse order.
+    ///
+    /// Only considers rule names that currently have at least one loaded rule, mirroring
+    /// `validate_rule_types`'s own `self.rules`-driven traversal of `self.rule_types`.
+    fn check_redundant_rule_types(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        for rule_name in self.rules.keys() {
+            if let Some(templates) = self.rule_types.get(rule_name) {
+                for i in 0..templates.len() {
+                    for j in (i + 1)..templates.len() {
+                        let (a, b) = (&templates[i], &templates[j]);
+                        if a.params.len() != b.params.len() {
+                            continue;
+                        }
+                        // Prefer "b is subsumed by a" when both orderings happen to match (e.g.
+                        // verbatim duplicate templates), so declaration order still breaks ties
+                        // the same way it did before both directions were checked.
+                        let (general, specific) = if matches!(
+                            self.rule_params_match(b, a),
+                            Ok(RuleParamMatch::True)
+                        ) {
+                            (a, b)
+                        } else if matches!(
+                            self.rule_params_match(a, b),
+                            Ok(RuleParamMatch::True)
+                        ) {
+                            (b, a)
+                        } else {
+                            continue;
+                        };
+                        let specific_text = specific.to_polar();
+                        self.cache_related_diagnostic_source(&specific_text, &general.body);
+                        diagnostics.push(Diagnostic::Warning(self.set_error_context(
+                            &specific.body,
+                            error::ValidationError::RedundantRuleType {
+                                rule_type: specific_text,
+                                shadowed_by: general.to_polar(),
+                            },
+                        )));
+                    }
+                }
+            }
+        }
+        diagnostics
+    }
+
+    /// Return the term whose specializer caused `rule` to fail to match `rule_type`, if any,
+    /// walking parameters left to right and stopping at the first mismatch. Used so validation
+    /// errors can underline the precise offending specializer (e.g. the `Foo` in `f(x: Foo)`)
+    /// instead of pointing at the whole rule body.
+    fn failing_param_span<'a>(&self, rule: &'a Rule, rule_type: &'a Rule) -> Option<&'a Term> {
+        rule.params
+            .iter()
+            .zip(rule_type.params.iter())
+            .enumerate()
+            .find_map(|(i, (rule_param, rule_type_param))| {
+                match self.check_param(i + 1, rule_param, rule_type_param) {
+                    Ok(RuleParamMatch::False(_)) => {
+                        Some(rule_param.specializer.as_ref().unwrap_or(&rule_param.parameter))
+                    }
+                    _ => None,
+                }
+            })
+    }
+
     /// Validate that all rules loaded into the knowledge base are valid based on rule types.
-    fn validate_rule_types(&self) -> PolarResult<()> {
+    ///
+    /// Collects every `InvalidRule`/`MissingRequiredRule` diagnostic across the whole KB in one
+    /// pass rather than bailing out at the first one -- a policy with several broken resource
+    /// blocks gets all of its problems reported together instead of forcing a fix-one-rerun loop.
+    /// A genuine internal failure from `rule_params_match` (as opposed to an ordinary "this rule
+    /// doesn't match its type" result) still aborts validation immediately, since it isn't a
+    /// property of the policy that more passes could usefully report alongside.
+    fn validate_rule_types(&self) -> PolarResult<Vec<Diagnostic>> {
+        self.validate_rule_types_scoped(None)
+    }
+
+    /// Like `validate_rule_types`, but when `names` is `Some`, only checks rules whose name is in
+    /// the set -- the rest of the KB's rules are skipped. `validate_rules_in_ranges` uses this to
+    /// scope validation to the rules touched by an edited source range.
+    fn validate_rule_types_scoped(
+        &self,
+        names: Option<&HashSet<Symbol>>,
+    ) -> PolarResult<Vec<Diagnostic>> {
+        let mut diagnostics = vec![];
+
         // For every rule, if there *is* a rule type, check that the rule matches the rule type.
         for (rule_name, generic_rule) in &self.rules {
+            if let Some(names) = names {
+                if !names.contains(rule_name) {
+                    continue;
+                }
+            }
             if let Some(types) = self.rule_types.get(rule_name) {
                 // If a type with the same name exists, then the parameters must match for each rule
                 for rule in generic_rule.rules.values() {
@@ -144,23 +709,50 @@ impl KnowledgeBase {
                                     ));
                                     false
                                 }
+                                RuleParamMatch::Failures(failures) => {
+                                    let details = failures
+                                        .iter()
+                                        .map(|(index, message)| {
+                                            format!("\tparameter {}: {}", index, message)
+                                        })
+                                        .collect::<Vec<_>>()
+                                        .join("\n");
+                                    msg.push_str(&format!(
+                                        "\n{}\n\tFailed to match because:\n{}\n",
+                                        rule_type.to_polar(),
+                                        details
+                                    ));
+                                    false
+                                }
                             })
                         })?;
                     if !found_match {
-                        return Err(self.set_error_context(
-                            &rule.body,
+                        // Point at the specific specializer that first failed to match, falling
+                        // back to the whole rule body if we can't pin one down (e.g. an arity
+                        // mismatch against every candidate rule type).
+                        let span = types
+                            .iter()
+                            .find_map(|rule_type| self.failing_param_span(rule, rule_type))
+                            .unwrap_or(&rule.body);
+                        diagnostics.push(Diagnostic::Error(self.set_error_context(
+                            span,
                             error::ValidationError::InvalidRule {
                                 rule: rule.to_polar(),
                                 msg,
                             },
-                        ));
+                        )));
                     }
                 }
             }
         }
 
         // For every rule type that is *required*, see that there is at least one corresponding
-        // implementation.
+        // implementation. Skipped in scoped mode: whether a required rule exists at all isn't a
+        // property of the edited range, so re-checking it on every keystroke would defeat the
+        // purpose of scoping.
+        if names.is_some() {
+            return Ok(diagnostics);
+        }
         for rule_type in self.rule_types.required_rule_types() {
             if let Some(GenericRule { rules, .. }) = self.rules.get(&rule_type.name) {
                 let mut found_match = false;
@@ -173,43 +765,141 @@ impl KnowledgeBase {
                     }
                 }
                 if !found_match {
-                    return Err(self.set_error_context(
-                        &rule_type.body,
+                    // Point at the specializer of whichever existing implementation came
+                    // closest, rather than the whole rule type, when one exists to compare
+                    // against.
+                    let span = rules
+                        .values()
+                        .find_map(|rule| self.failing_param_span(rule, rule_type))
+                        .unwrap_or(&rule_type.body);
+                    self.cache_missing_rule_suggestion(&rule_type.name);
+                    diagnostics.push(Diagnostic::Error(self.set_error_context(
+                        span,
                         error::ValidationError::MissingRequiredRule {
                             rule: rule_type.clone(),
                         },
-                    ));
+                    )));
                 }
             } else {
-                return Err(self.set_error_context(
+                self.cache_missing_rule_suggestion(&rule_type.name);
+                diagnostics.push(Diagnostic::Error(self.set_error_context(
                     &rule_type.body,
                     error::ValidationError::MissingRequiredRule {
                         rule: rule_type.clone(),
                     },
-                ));
+                )));
             }
         }
 
-        Ok(())
+        Ok(diagnostics)
     }
 
-    /// Determine whether the fields of a rule parameter specializer match the fields of a type parameter specializer.
-    /// Rule fields match if they are a superset of type fields and all field values are equal.
-    // TODO: once field-level specializers are working this should be updated so
-    // that it recursively checks all fields match, rather than checking for
-    // equality
+    /// Determine whether the fields of a rule parameter specializer match the fields of a type
+    /// parameter specializer. Rule fields match if they are a superset of type fields and every
+    /// field value unifies, recursively, with the corresponding type field value.
     fn param_fields_match(&self, type_fields: &Dictionary, rule_fields: &Dictionary) -> bool {
-        return type_fields
-            .fields
-            .iter()
-            .map(|(k, type_value)| {
-                rule_fields
-                    .fields
-                    .get(k)
-                    .map(|rule_value| rule_value == type_value)
-                    .unwrap_or_else(|| false)
-            })
-            .all(|v| v);
+        self.unify_fields(type_fields, rule_fields, &mut FieldBindings::new())
+            .is_ok()
+    }
+
+    /// Recursively unify a rule-type specializer's fields against a rule specializer's fields,
+    /// accumulating `bindings` for any `Variable`s encountered on the type side.
+    ///
+    /// This walks both `Dictionary`s key-by-key the way `could_unify` walks terms during type
+    /// inference: `Dictionary`s unify field-wise, `List`s unify element-wise by position (the
+    /// rule's list must be at least as long as the type's), `InstanceLiteral`s unify
+    /// iff their tags are MRO-compatible and their nested fields unify, and a `Variable` on the
+    /// type side is a placeholder that unifies with any value while recording a binding -- a
+    /// second occurrence of the same variable (e.g. `{x: v, y: v}`) must agree with the first.
+    /// Returns the mismatch path on failure.
+    fn unify_fields(
+        &self,
+        type_fields: &Dictionary,
+        rule_fields: &Dictionary,
+        bindings: &mut FieldBindings,
+    ) -> Result<(), String> {
+        for (k, type_value) in &type_fields.fields {
+            let rule_value = rule_fields
+                .fields
+                .get(k)
+                .ok_or_else(|| format!("missing field `{}`", k))?;
+            self.unify_values(type_value, rule_value, bindings)
+                .map_err(|reason| format!("field `{}`: {}", k, reason))?;
+        }
+        Ok(())
+    }
+
+    /// Unify a single type-side value against the corresponding rule-side value. See
+    /// [`KnowledgeBase::unify_fields`] for the structural rules.
+    fn unify_values(
+        &self,
+        type_value: &Term,
+        rule_value: &Term,
+        bindings: &mut FieldBindings,
+    ) -> Result<(), String> {
+        match (type_value.value(), rule_value.value()) {
+            (Value::Variable(var), _) => match bindings.get(var) {
+                Some(bound) if bound == rule_value => Ok(()),
+                Some(bound) => Err(format!(
+                    "variable `{}` bound to {} does not agree with {}",
+                    var,
+                    bound.to_polar(),
+                    rule_value.to_polar()
+                )),
+                None => {
+                    bindings.insert(var.clone(), rule_value.clone());
+                    Ok(())
+                }
+            },
+            (Value::Dictionary(type_dict), Value::Dictionary(rule_dict))
+            | (Value::Pattern(Pattern::Dictionary(type_dict)), Value::Dictionary(rule_dict))
+            | (Value::Dictionary(type_dict), Value::Pattern(Pattern::Dictionary(rule_dict)))
+            | (
+                Value::Pattern(Pattern::Dictionary(type_dict)),
+                Value::Pattern(Pattern::Dictionary(rule_dict)),
+            ) => self.unify_fields(type_dict, rule_dict, bindings),
+            (Value::List(type_list), Value::List(rule_list)) => {
+                // Unify by position: the rule's list must be at least as long as the type's list,
+                // and each type element must unify with the rule element at the same index.
+                if type_list.len() > rule_list.len() {
+                    return Err(format!(
+                        "list {} is not as specific as {}",
+                        rule_value.to_polar(),
+                        type_value.to_polar()
+                    ));
+                }
+                for (type_elem, rule_elem) in type_list.iter().zip(rule_list.iter()) {
+                    self.unify_values(type_elem, rule_elem, bindings)?;
+                }
+                Ok(())
+            }
+            (
+                Value::Pattern(Pattern::Instance(type_instance)),
+                Value::Pattern(Pattern::Instance(rule_instance)),
+            ) => {
+                match self.check_rule_instance_is_subclass_of_rule_type_instance(
+                    rule_instance,
+                    type_instance,
+                    0,
+                ) {
+                    Ok(RuleParamMatch::True) => (),
+                    Ok(RuleParamMatch::False(msg)) => return Err(msg),
+                    Err(e) => return Err(e.to_string()),
+                }
+                self.unify_fields(&type_instance.fields, &rule_instance.fields, bindings)
+            }
+            (_, _) => {
+                if type_value == rule_value {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "{} does not match {}",
+                        rule_value.to_polar(),
+                        type_value.to_polar()
+                    ))
+                }
+            }
+        }
     }
 
     /// Use MRO lists passed in from host library to determine if one `InstanceLiteral` pattern is
@@ -225,7 +915,7 @@ impl KnowledgeBase {
         // TODO(gj): make actual term available here instead of constructing a fake test one.
         let term = self.get_registered_class(&term!(rule_type_instance.tag.clone()))?;
         if let Value::ExternalInstance(ExternalInstance { instance_id, .. }) = term.value() {
-            if let Some(rule_mro) = self.mro.get(&rule_instance.tag) {
+            if let Some(rule_mro) = self.mro.get(self.resolve_alias(&rule_instance.tag)) {
                 if !rule_mro.contains(instance_id) {
                     Ok(RuleParamMatch::False(format!(
                         "Rule specializer {} on parameter {} must match rule type specializer {}",
@@ -252,6 +942,10 @@ impl KnowledgeBase {
     }
 
     /// Check that a rule parameter that has a pattern specializer matches a rule type parameter that has a pattern specializer.
+    ///
+    /// A rule type specializer may be a `Pattern::Logical` combinator (`and`/`or`/`not`) over a
+    /// set of operand specializers, in which case the rule specializer is checked against each
+    /// operand according to the combinator's semantics.
     fn check_pattern_param(
         &self,
         index: usize,
@@ -259,6 +953,9 @@ impl KnowledgeBase {
         rule_type_pattern: &Pattern,
     ) -> PolarResult<RuleParamMatch> {
         Ok(match (rule_type_pattern, rule_pattern) {
+            (Pattern::Logical(op, operands), _) => {
+                self.check_logical_pattern_param(index, rule_pattern, op, operands)?
+            }
             (Pattern::Instance(rule_type_instance), Pattern::Instance(rule_instance)) => {
                 // if tags match, all rule type fields must match those in rule fields, otherwise false
                 if rule_type_instance.tag == rule_instance.tag {
@@ -283,9 +980,17 @@ impl KnowledgeBase {
                                 return Ok(RuleParamMatch::False(format!("Rule specializer {} on parameter {} did not match rule type specializer {} because the specializer fields did not match.", rule_instance.to_polar(), index, rule_type_instance.to_polar())));
                             }
                         } else {
-                            // TODO(gj): revisit when we have unions beyond Actor & Resource. Union
-                            // A matches union B if union A is a member of union B.
-                            return Ok(RuleParamMatch::False(format!("Rule specializer {} on parameter {} does not match rule type specializer {}", rule_instance.tag, index, rule_type_instance.tag)));
+                            // General union-of-unions membership: union A matches union B if
+                            // every concrete member reachable from A is also reachable from B,
+                            // following nested union membership transitively.
+                            let rule_members = self.resolve_union_members(&rule_instance.tag);
+                            let rule_type_members =
+                                self.resolve_union_members(&rule_type_instance.tag);
+                            return Ok(if rule_members.is_subset(&rule_type_members) {
+                                RuleParamMatch::True
+                            } else {
+                                RuleParamMatch::False(format!("Rule specializer {} on parameter {} is not a member of rule type specializer {}", rule_instance.tag, index, rule_type_instance.tag))
+                            });
                         }
                     }
 
@@ -309,19 +1014,10 @@ impl KnowledgeBase {
                         }
                         if !success {
                             let mut err = format!("Rule specializer {} on parameter {} must be a member of rule type specializer {}", rule_instance.tag,index, rule_type_instance.tag);
-                            if rule_type_instance.tag.0 == ACTOR_UNION_NAME {
-                                err.push_str(&format!("
-
-\tPerhaps you meant to add an actor block to the top of your policy, like this:
-
-\t  actor {} {{}}", rule_instance.tag));
-                            } else if rule_type_instance.tag.0 == RESOURCE_UNION_NAME {
-                                err.push_str(&format!("
-
-\tPerhaps you meant to add a resource block to your policy, like this:
-
-\t  resource {} {{ .. }}", rule_instance.tag));
-                            }
+                            err.push_str(&Self::union_declaration_hint(
+                                &rule_type_instance.tag,
+                                &rule_instance.tag,
+                            ));
 
                             return Ok(RuleParamMatch::False(err));
                         }
@@ -363,6 +1059,76 @@ impl KnowledgeBase {
         })
     }
 
+    /// Evaluate a `Pattern::Logical` rule type specializer against a rule specializer.
+    ///
+    /// `Or` matches if any operand matches, collecting every operand's failure message so the
+    /// "Must match one of the following rule types" error text stays useful when none do. `And`
+    /// requires every operand to match. `Not` inverts the result of its single operand.
+    fn check_logical_pattern_param(
+        &self,
+        index: usize,
+        rule_pattern: &Pattern,
+        op: &LogicalOp,
+        operands: &[Pattern],
+    ) -> PolarResult<RuleParamMatch> {
+        Ok(match op {
+            LogicalOp::Not => {
+                let operand = operands
+                    .first()
+                    .expect("`not` rule type specializer must have exactly one operand");
+                match self.check_pattern_param(index, rule_pattern, operand)? {
+                    RuleParamMatch::True => RuleParamMatch::False(format!(
+                        "Rule specializer {} on parameter {} must not match rule type specializer {}",
+                        rule_pattern.to_polar(),
+                        index,
+                        operand.to_polar()
+                    )),
+                    RuleParamMatch::False(_) => RuleParamMatch::True,
+                }
+            }
+            LogicalOp::And => {
+                let mut failures = vec![];
+                for operand in operands {
+                    if let RuleParamMatch::False(msg) =
+                        self.check_pattern_param(index, rule_pattern, operand)?
+                    {
+                        failures.push(msg);
+                    }
+                }
+                if failures.is_empty() {
+                    RuleParamMatch::True
+                } else {
+                    RuleParamMatch::False(format!(
+                        "Rule specializer {} on parameter {} must match all of the following rule type specializers:\n{}",
+                        rule_pattern.to_polar(),
+                        index,
+                        failures.join("\n")
+                    ))
+                }
+            }
+            LogicalOp::Or => {
+                let mut failures = vec![];
+                let mut matched = false;
+                for operand in operands {
+                    match self.check_pattern_param(index, rule_pattern, operand)? {
+                        RuleParamMatch::True => matched = true,
+                        RuleParamMatch::False(msg) => failures.push(msg),
+                    }
+                }
+                if matched {
+                    RuleParamMatch::True
+                } else {
+                    RuleParamMatch::False(format!(
+                        "Rule specializer {} on parameter {} must match one of the following rule type specializers:\n{}",
+                        rule_pattern.to_polar(),
+                        index,
+                        failures.join("\n")
+                    ))
+                }
+            }
+        })
+    }
+
     /// Check that a rule parameter that is a value matches a rule type parameter that is a value
     fn check_value_param(
         &self,
@@ -527,7 +1293,61 @@ impl KnowledgeBase {
         )
     }
 
+    /// If `tag` names a rule-type equality-binding wildcard (e.g. `$T`), return its bare name
+    /// (`T`). Every occurrence of the same wildcard name within a rule type's parameter list must
+    /// resolve to the same concrete class across the matching rule's corresponding parameters.
+    fn wildcard_name(tag: &Symbol) -> Option<&str> {
+        tag.0.strip_prefix('$')
+    }
+
+    /// Check a single rule parameter against a rule type parameter, resolving equality-binding
+    /// wildcard specializers (`$T`) against `wildcard_bindings` as they're encountered.
+    ///
+    /// The first occurrence of a given wildcard name binds it to the concrete class of the rule's
+    /// corresponding specializer; every later occurrence of that name requires the rule's
+    /// corresponding parameter to be a subclass-compatible instance of the already-bound class.
+    /// Parameters without a wildcard specializer fall through to the ordinary `check_param` rules.
+    fn check_param_with_wildcards(
+        &self,
+        index: usize,
+        rule_param: &Parameter,
+        rule_type_param: &Parameter,
+        wildcard_bindings: &mut HashMap<String, Symbol>,
+    ) -> PolarResult<RuleParamMatch> {
+        if let Some(Value::Pattern(Pattern::Instance(InstanceLiteral { tag, .. }))) =
+            rule_type_param.specializer.as_ref().map(Term::value)
+        {
+            if let Some(name) = Self::wildcard_name(tag) {
+                let rule_instance = match rule_param.specializer.as_ref().map(Term::value) {
+                    Some(Value::Pattern(Pattern::Instance(instance))) => instance,
+                    _ => {
+                        return Ok(RuleParamMatch::False(format!(
+                            "Invalid parameter {}. Rule type wildcard ${} requires an instance specializer.",
+                            index, name
+                        )));
+                    }
+                };
+                return Ok(match wildcard_bindings.get(name) {
+                    Some(bound_class) => self.check_rule_instance_is_subclass_of_rule_type_instance(
+                        rule_instance,
+                        &instance!(bound_class.clone()),
+                        index,
+                    )?,
+                    None => {
+                        wildcard_bindings.insert(name.to_string(), rule_instance.tag.clone());
+                        RuleParamMatch::True
+                    }
+                });
+            }
+        }
+        self.check_param(index, rule_param, rule_type_param)
+    }
+
     /// Determine whether a `rule` matches a `rule_type` based on its parameters.
+    ///
+    /// Every parameter is checked -- a mismatch on one parameter doesn't short-circuit checking
+    /// the rest -- so a caller sees every offending parameter via `RuleParamMatch::Failures`
+    /// rather than just the first one encountered.
     fn rule_params_match(&self, rule: &Rule, rule_type: &Rule) -> PolarResult<RuleParamMatch> {
         if rule.params.len() != rule_type.params.len() {
             return Ok(RuleParamMatch::False(format!(
@@ -536,51 +1356,592 @@ impl KnowledgeBase {
                 rule_type.params.len()
             )));
         }
-        let mut failure_message = "".to_owned();
-        rule.params
+        let mut wildcard_bindings = HashMap::new();
+        let results = rule
+            .params
             .iter()
             .zip(rule_type.params.iter())
             .enumerate()
             .map(|(i, (rule_param, rule_type_param))| {
-                self.check_param(i + 1, rule_param, rule_type_param)
+                self.check_param_with_wildcards(i + 1, rule_param, rule_type_param, &mut wildcard_bindings)
+                    .map(|result| (i + 1, result))
             })
-            .collect::<PolarResult<Vec<RuleParamMatch>>>()
-            .map(|results| {
-                // TODO(gj): all() is short-circuiting -- do we want to gather up *all* failure
-                // messages instead of just the first one?
-                results.iter().all(|r| {
-                    if let RuleParamMatch::False(msg) = r {
-                        failure_message = msg.to_owned();
-                        false
+            .collect::<PolarResult<Vec<(usize, RuleParamMatch)>>>()?;
+
+        let failures: Vec<(usize, String)> = results
+            .into_iter()
+            .filter_map(|(index, result)| match result {
+                RuleParamMatch::False(msg) => Some((index, msg)),
+                RuleParamMatch::Failures(mut fs) => {
+                    if fs.is_empty() {
+                        None
                     } else {
-                        true
+                        Some(fs.remove(0))
                     }
-                })
-            })
-            .map(|matched| {
-                if matched {
-                    RuleParamMatch::True
-                } else {
-                    RuleParamMatch::False(failure_message)
                 }
+                RuleParamMatch::True => None,
             })
-    }
-
-    pub fn get_rules(&self) -> &HashMap<Symbol, GenericRule> {
-        &self.rules
-    }
+            .collect();
 
-    #[cfg(test)]
-    pub fn get_rule_types(&self, name: &Symbol) -> Option<&Vec<Rule>> {
-        self.rule_types.get(name)
+        Ok(if failures.is_empty() {
+            RuleParamMatch::True
+        } else {
+            RuleParamMatch::Failures(failures)
+        })
     }
 
-    pub fn get_generic_rule(&self, name: &Symbol) -> Option<&GenericRule> {
-        self.rules.get(name)
+    /// Opt-in counterpart to `rule_params_match` that reports *why* each mismatched parameter
+    /// failed as a structured `MatchFailureReason` instead of a plain-text message. Not called by
+    /// `validate_rule_types`'s normal evaluation path, so the latter's performance is unaffected;
+    /// use this when a caller (e.g. an editor surfacing diagnostics) needs to inspect the failure
+    /// programmatically rather than just display it.
+    pub fn rule_params_match_reasons(
+        &self,
+        rule: &Rule,
+        rule_type: &Rule,
+    ) -> PolarResult<Vec<MatchFailureReason>> {
+        if rule.params.len() != rule_type.params.len() {
+            return Ok(vec![MatchFailureReason::ArityMismatch {
+                expected: rule_type.params.len(),
+                got: rule.params.len(),
+            }]);
+        }
+        rule.params
+            .iter()
+            .zip(rule_type.params.iter())
+            .enumerate()
+            .map(|(i, (rule_param, rule_type_param))| {
+                match self.check_param(i + 1, rule_param, rule_type_param)? {
+                    RuleParamMatch::True => Ok(None),
+                    _ => Ok(Some(self.classify_param_mismatch(
+                        i + 1,
+                        rule_param,
+                        rule_type_param,
+                    ))),
+                }
+            })
+            .collect::<PolarResult<Vec<Option<MatchFailureReason>>>>()
+            .map(|reasons| reasons.into_iter().flatten().collect())
     }
 
-    pub fn add_rule_type(&mut self, rule_type: Rule) {
-        self.rule_types.add(rule_type);
+    /// Classify *why* `rule_param` failed to match `rule_type_param`, mirroring the branches
+    /// `check_param` uses to decide pass/fail but producing a `MatchFailureReason` instead of a
+    /// message string. Only meaningful to call on a pair already known to have failed.
+    fn classify_param_mismatch(
+        &self,
+        index: usize,
+        rule_param: &Parameter,
+        rule_type_param: &Parameter,
+    ) -> MatchFailureReason {
+        match (
+            rule_type_param.specializer.as_ref().map(Term::value),
+            rule_param.specializer.as_ref().map(Term::value),
+        ) {
+            // Rule type requires an instance-of-class specializer; the rule has none at all.
+            (Some(Value::Pattern(Pattern::Instance(InstanceLiteral { tag, .. }))), None) => {
+                MatchFailureReason::SpecializerTypeMismatch {
+                    param_index: index,
+                    expected_type: tag.0.clone(),
+                    actual_value: "<no specializer>".to_owned(),
+                }
+            }
+            // Both sides specialize on a class with different tags; the rule's class isn't a
+            // registered subclass of the rule type's class.
+            (
+                Some(Value::Pattern(Pattern::Instance(InstanceLiteral {
+                    tag: expected, ..
+                }))),
+                Some(Value::Pattern(Pattern::Instance(InstanceLiteral { tag: actual, .. }))),
+            ) if expected != actual => MatchFailureReason::NotASubclass {
+                param_index: index,
+                class: actual.0.clone(),
+                required: expected.0.clone(),
+            },
+            // Same class on both sides -- the mismatch is in the instance's fields.
+            (
+                Some(Value::Pattern(Pattern::Instance(InstanceLiteral { fields: expected, .. }))),
+                Some(Value::Pattern(Pattern::Instance(InstanceLiteral { fields: actual, .. }))),
+            ) => expected
+                .fields
+                .keys()
+                .find(|field| !actual.fields.contains_key(*field))
+                .map(|field| MatchFailureReason::MissingDictField {
+                    param_index: index,
+                    field: field.0.clone(),
+                })
+                .unwrap_or(MatchFailureReason::Other {
+                    param_index: index,
+                    message: "instance specializer fields did not unify".to_owned(),
+                }),
+            // Both sides specialize on a dictionary; report the first field the rule is missing.
+            (
+                Some(Value::Pattern(Pattern::Dictionary(expected))),
+                Some(Value::Pattern(Pattern::Dictionary(actual))),
+            )
+            | (Some(Value::Pattern(Pattern::Dictionary(expected))), Some(Value::Dictionary(actual))) => expected
+                .fields
+                .keys()
+                .find(|field| !actual.fields.contains_key(*field))
+                .map(|field| MatchFailureReason::MissingDictField {
+                    param_index: index,
+                    field: field.0.clone(),
+                })
+                .unwrap_or(MatchFailureReason::Other {
+                    param_index: index,
+                    message: "dictionary specializer fields did not unify".to_owned(),
+                }),
+            _ => MatchFailureReason::Other {
+                param_index: index,
+                message: format!(
+                    "rule parameter {} does not match rule type parameter {}",
+                    rule_param.to_polar(),
+                    rule_type_param.to_polar()
+                ),
+            },
+        }
+    }
+
+    pub fn get_rules(&self) -> &HashMap<Symbol, GenericRule> {
+        &self.rules
+    }
+
+    #[cfg(test)]
+    pub fn get_rule_types(&self, name: &Symbol) -> Option<&Vec<Rule>> {
+        self.rule_types.get(name)
+    }
+
+    pub fn get_generic_rule(&self, name: &Symbol) -> Option<&GenericRule> {
+        self.rules.get(name)
+    }
+
+    pub fn add_rule_type(&mut self, rule_type: Rule) {
+        self.rule_types.add(rule_type);
+    }
+
+    /// Return every loaded rule matching `pattern`, an SSR-style template such as
+    /// `"allow($actor, \"read\", $resource: Document)"` where `$name` tokens are metavariables
+    /// that bind to any concrete term, optionally constrained by a `: Type` annotation checked
+    /// with the same MRO-aware specializer logic as `rule_params_match` (so `$resource: Document`
+    /// also matches subclasses of `Document`). A metavariable used more than once must bind to
+    /// structurally equal terms in every position where it appears.
+    ///
+    /// A rule matches if `pattern` matches its head (name + params; specializers are checked only
+    /// for typed metavariables), or
+    /// failing that, if `pattern`'s name and arity match some predicate call found anywhere in its
+    /// body (e.g. `"is_public($resource)"` matching the `is_public(resource)` conjunct of
+    /// `allow(actor, action, resource) if is_public(resource);`) -- searched with `walk_term` so
+    /// every nested position (inside `and`, dict values, etc.) is reachable. Call arguments have
+    /// no specializer to check a typed metavariable against, so `$name: Type` only ever matches
+    /// against the head.
+    pub fn search_rules(&self, pattern: &str) -> PolarResult<Vec<&Rule>> {
+        let template = Self::parse_ssr_template(pattern)?;
+        Ok(self
+            .rules
+            .values()
+            .flat_map(|generic| generic.rules.values())
+            .filter(|rule| self.match_ssr_template(rule, &template).is_some())
+            .map(AsRef::as_ref)
+            .collect())
+    }
+
+    /// Rewrite every loaded rule whose head matches `pattern` by substituting the captured
+    /// metavariable bindings into `replacement`'s head (e.g. `"has_role($actor, $role, $resource)"`),
+    /// preserving each rewritten rule's original body modulo variable renaming implied by the
+    /// bindings. Returns the number of rules rewritten.
+    pub fn rewrite_rules(&mut self, pattern: &str, replacement: &str) -> PolarResult<usize> {
+        let search = Self::parse_ssr_template(pattern)?;
+        let replace = Self::parse_ssr_template(replacement)?;
+
+        let all_rules: Vec<Rule> = self
+            .rules
+            .values()
+            .flat_map(|generic| generic.rules.values())
+            .map(|rule| (**rule).clone())
+            .collect();
+
+        let mut rewritten = 0;
+        self.rules.clear();
+        for rule in all_rules {
+            match self.match_ssr_head(&rule, &search) {
+                Some((bindings, var_aliases)) => {
+                    rewritten += 1;
+                    let new_rule =
+                        Self::substitute_ssr_template(&replace, &bindings, &var_aliases, &rule);
+                    self.add_rule(new_rule);
+                }
+                None => self.add_rule(rule),
+            }
+        }
+        Ok(rewritten)
+    }
+
+    /// Like `rewrite_rules`, but non-mutating: returns the would-be rewritten rule paired with its
+    /// rendered source for every matching rule, without touching the KB. Lets a caller (e.g. an
+    /// editor applying a refactor) preview or selectively apply the edits instead of having them
+    /// take effect immediately.
+    pub fn plan_rewrite(&self, pattern: &str, replacement: &str) -> PolarResult<Vec<(Rule, String)>> {
+        let search = Self::parse_ssr_template(pattern)?;
+        let replace = Self::parse_ssr_template(replacement)?;
+
+        Ok(self
+            .rules
+            .values()
+            .flat_map(|generic| generic.rules.values())
+            .filter_map(|rule| {
+                self.match_ssr_head(rule, &search)
+                    .map(|(bindings, var_aliases)| {
+                        let new_rule =
+                            Self::substitute_ssr_template(&replace, &bindings, &var_aliases, rule);
+                        let source = new_rule.to_polar();
+                        (new_rule, source)
+                    })
+            })
+            .collect())
+    }
+
+    /// Parse a `name(arg, arg, ...)` SSR template into its call name and flat list of argument
+    /// patterns. `$foo` tokens become metavariables; bare identifiers become exact-variable
+    /// matches; quoted strings, integers and booleans become literal value matches. Any other
+    /// token (including a float such as `1.5`) is treated as a bare identifier.
+    fn parse_ssr_template(template: &str) -> PolarResult<SsrTemplate> {
+        let template = template.trim();
+        let open = template.find('(').ok_or_else(|| {
+            error::RuntimeError::Unsupported {
+                msg: format!("Invalid SSR template `{}`: expected `name(args...)`.", template),
+            }
+            .into()
+        })?;
+        if !template.ends_with(')') {
+            return Err(error::RuntimeError::Unsupported {
+                msg: format!("Invalid SSR template `{}`: missing closing `)`.", template),
+            }
+            .into());
+        }
+        let name = template[..open].trim();
+        if name.is_empty() {
+            return Err(error::RuntimeError::Unsupported {
+                msg: format!("Invalid SSR template `{}`: missing rule name.", template),
+            }
+            .into());
+        }
+        let args_src = &template[open + 1..template.len() - 1];
+        let params = if args_src.trim().is_empty() {
+            vec![]
+        } else {
+            args_src
+                .split(',')
+                .map(|arg| Self::parse_ssr_term(arg.trim()))
+                .collect()
+        };
+        Ok(SsrTemplate {
+            name: sym!(name),
+            params,
+        })
+    }
+
+    /// Parse a single SSR argument token into a metavariable (optionally typed via `$name: Type`),
+    /// exact-variable, or literal pattern.
+    fn parse_ssr_term(token: &str) -> SsrTerm {
+        if let Some(rest) = token.strip_prefix('$') {
+            match rest.split_once(':') {
+                Some((name, ty)) => SsrTerm::Metavar(name.trim().to_owned(), Some(sym!(ty.trim()))),
+                None => SsrTerm::Metavar(rest.to_owned(), None),
+            }
+        } else if token.starts_with('"') && token.ends_with('"') && token.len() >= 2 {
+            SsrTerm::Value(term!(token[1..token.len() - 1].to_owned()))
+        } else if let Ok(i) = token.parse::<i64>() {
+            SsrTerm::Value(term!(i))
+        } else if token == "true" || token == "false" {
+            SsrTerm::Value(term!(token == "true"))
+        } else {
+            SsrTerm::Var(sym!(token))
+        }
+    }
+
+    /// Match `rule` against `template`: first its head (name + params; specializers are checked
+    /// only for typed metavariables), and
+    /// failing that, any predicate call matching `template`'s name/arity found anywhere in its
+    /// body. On success, returns the captured metavariable bindings plus a reverse map from each
+    /// matched variable to the metavariable name it was bound under, so a rewrite can rename
+    /// occurrences of that variable in the rule body.
+    fn match_ssr_template(
+        &self,
+        rule: &Rule,
+        template: &SsrTemplate,
+    ) -> Option<(HashMap<String, Term>, HashMap<Symbol, String>)> {
+        self.match_ssr_head(rule, template)
+            .or_else(|| self.find_ssr_call_in_body(&rule.body, template))
+    }
+
+    /// Match `rule`'s head (name + params) against `template`. Specializers are checked only for
+    /// typed metavariables.
+    fn match_ssr_head(
+        &self,
+        rule: &Rule,
+        template: &SsrTemplate,
+    ) -> Option<(HashMap<String, Term>, HashMap<Symbol, String>)> {
+        if rule.name != template.name || rule.params.len() != template.params.len() {
+            return None;
+        }
+        let mut bindings = HashMap::new();
+        let mut var_aliases = HashMap::new();
+        for (index, (param, pattern)) in rule.params.iter().zip(template.params.iter()).enumerate() {
+            match pattern {
+                SsrTerm::Metavar(name, ty) => {
+                    if let Some(ty) = ty {
+                        let rule_type_param = Parameter {
+                            parameter: term!(sym!("_")),
+                            specializer: Some(pattern!(instance!(ty.clone()))),
+                        };
+                        match self.check_param(index + 1, param, &rule_type_param) {
+                            Ok(RuleParamMatch::True) => {}
+                            _ => return None,
+                        }
+                    }
+                    if !Self::bind_ssr_metavar(
+                        name,
+                        &param.parameter,
+                        &mut bindings,
+                        &mut var_aliases,
+                    ) {
+                        return None;
+                    }
+                }
+                SsrTerm::Var(expected) => match param.parameter.value() {
+                    Value::Variable(actual) if actual == expected => {}
+                    _ => return None,
+                },
+                SsrTerm::Value(expected) => {
+                    if param.specializer.is_some() || &param.parameter != expected {
+                        return None;
+                    }
+                }
+            }
+        }
+        Some((bindings, var_aliases))
+    }
+
+    /// Search `body` for a predicate call -- e.g. `is_public(resource)` as a conjunct of
+    /// `allow(actor, action, resource) if is_public(resource);` -- whose name and argument
+    /// patterns match `template`. Uses `Visitor`/`walk_term` so every nested position (inside
+    /// `and`, dict values, etc.) is reachable, rather than hand-rolling the traversal.
+    fn find_ssr_call_in_body(
+        &self,
+        body: &Term,
+        template: &SsrTemplate,
+    ) -> Option<(HashMap<String, Term>, HashMap<Symbol, String>)> {
+        struct FindCall<'a> {
+            template: &'a SsrTemplate,
+            found: Option<(HashMap<String, Term>, HashMap<Symbol, String>)>,
+        }
+
+        impl<'a> Visitor for FindCall<'a> {
+            fn visit_term(&mut self, t: &Term) {
+                if self.found.is_some() {
+                    return;
+                }
+                if let Value::Call(Call { name, args, .. }) = t.value() {
+                    if name == &self.template.name {
+                        self.found = KnowledgeBase::match_ssr_call_args(args, self.template);
+                        if self.found.is_some() {
+                            return;
+                        }
+                    }
+                }
+                walk_term(self, t)
+            }
+        }
+
+        let mut finder = FindCall {
+            template,
+            found: None,
+        };
+        finder.visit_term(body);
+        finder.found
+    }
+
+    /// Match a call's flat argument list against `template`'s parameter patterns. Plain call
+    /// arguments carry no specializer, so a typed metavariable (`$foo: Type`) never matches here.
+    fn match_ssr_call_args(
+        args: &[Term],
+        template: &SsrTemplate,
+    ) -> Option<(HashMap<String, Term>, HashMap<Symbol, String>)> {
+        if args.len() != template.params.len() {
+            return None;
+        }
+        let mut bindings = HashMap::new();
+        let mut var_aliases = HashMap::new();
+        for (arg, pattern) in args.iter().zip(template.params.iter()) {
+            match pattern {
+                SsrTerm::Metavar(name, ty) => {
+                    if ty.is_some() {
+                        return None;
+                    }
+                    if !Self::bind_ssr_metavar(name, arg, &mut bindings, &mut var_aliases) {
+                        return None;
+                    }
+                }
+                SsrTerm::Var(expected) => match arg.value() {
+                    Value::Variable(actual) if actual == expected => {}
+                    _ => return None,
+                },
+                SsrTerm::Value(expected) => {
+                    if arg != expected {
+                        return None;
+                    }
+                }
+            }
+        }
+        Some((bindings, var_aliases))
+    }
+
+    /// Bind metavariable `name` to `concrete`, shared by head-param and call-arg matching.
+    /// Returns `false` (meaning the overall match fails) if `name` was already bound to a
+    /// different term.
+    fn bind_ssr_metavar(
+        name: &str,
+        concrete: &Term,
+        bindings: &mut HashMap<String, Term>,
+        var_aliases: &mut HashMap<Symbol, String>,
+    ) -> bool {
+        match bindings.get(name) {
+            Some(bound) if bound == concrete => {}
+            Some(_) => return false,
+            None => {
+                bindings.insert(name.to_owned(), concrete.clone());
+            }
+        }
+        if let Value::Variable(var) = concrete.value() {
+            var_aliases.insert(var.clone(), name.to_owned());
+        }
+        true
+    }
+
+    /// Build the rewritten rule for a successful SSR match: `matched`'s non-head fields (source
+    /// info, etc.) are preserved, its name/params come from `replace` with metavariables resolved
+    /// against `bindings`, and its body is carried over with any occurrence of a matched-and-bound
+    /// variable renamed to the term its metavariable is now bound to.
+    fn substitute_ssr_template(
+        replace: &SsrTemplate,
+        bindings: &HashMap<String, Term>,
+        var_aliases: &HashMap<Symbol, String>,
+        matched: &Rule,
+    ) -> Rule {
+        let mut new_rule = matched.clone();
+        new_rule.name = replace.name.clone();
+        new_rule.params = replace
+            .params
+            .iter()
+            .enumerate()
+            .map(|(i, pattern)| {
+                let parameter = match pattern {
+                    SsrTerm::Metavar(name, _) => bindings
+                        .get(name)
+                        .cloned()
+                        .unwrap_or_else(|| term!(sym!(name))),
+                    SsrTerm::Var(sym) => term!(sym.clone()),
+                    SsrTerm::Value(v) => v.clone(),
+                };
+                // A metavariable carries over the specializer of the matched head parameter it
+                // was bound to; any other position takes the one at the same index.
+                let specializer = match pattern {
+                    SsrTerm::Metavar(name, _) => bindings
+                        .get(name)
+                        .and_then(|bound| matched.params.iter().find(|p| &p.parameter == bound))
+                        .and_then(|p| p.specializer.clone()),
+                    _ => matched.params.get(i).and_then(|p| p.specializer.clone()),
+                };
+                Parameter {
+                    parameter,
+                    specializer,
+                }
+            })
+            .collect();
+        new_rule.body = Self::rename_vars_in_term(&matched.body, bindings, var_aliases);
+        new_rule
+    }
+
+    /// Recursively rewrite `term`, replacing every `Variable`/`RestVariable` that was bound to a
+    /// metavariable during matching with that metavariable's current binding. Walks every shape a
+    /// metavariable-bound variable could be nested inside -- not just `Expression`/`List`, but
+    /// `Dictionary` field values and `Pattern` fields/operands too -- since a rule body can
+    /// reference a matched variable from inside a dict literal or specializer pattern just as
+    /// easily as from inside a conjunction.
+    fn rename_vars_in_term(
+        term: &Term,
+        bindings: &HashMap<String, Term>,
+        var_aliases: &HashMap<Symbol, String>,
+    ) -> Term {
+        let renamed_var = |var: &Symbol| -> Option<Term> {
+            var_aliases.get(var).and_then(|name| bindings.get(name)).cloned()
+        };
+        match term.value() {
+            Value::Variable(var) | Value::RestVariable(var) => {
+                renamed_var(var).unwrap_or_else(|| term.clone())
+            }
+            Value::Expression(Operation { operator, args }) => {
+                let args = args
+                    .iter()
+                    .map(|arg| Self::rename_vars_in_term(arg, bindings, var_aliases))
+                    .collect();
+                term.clone_with_value(Value::Expression(Operation {
+                    operator: *operator,
+                    args,
+                }))
+            }
+            Value::List(items) => {
+                let items = items
+                    .iter()
+                    .map(|item| Self::rename_vars_in_term(item, bindings, var_aliases))
+                    .collect();
+                term.clone_with_value(Value::List(items))
+            }
+            Value::Dictionary(dict) => term.clone_with_value(Value::Dictionary(
+                Self::rename_vars_in_dictionary(dict, bindings, var_aliases),
+            )),
+            Value::Pattern(pattern) => term.clone_with_value(Value::Pattern(
+                Self::rename_vars_in_pattern(pattern, bindings, var_aliases),
+            )),
+            _ => term.clone(),
+        }
+    }
+
+    /// `rename_vars_in_term` for a `Dictionary`'s field values; keys are never variables.
+    fn rename_vars_in_dictionary(
+        dict: &Dictionary,
+        bindings: &HashMap<String, Term>,
+        var_aliases: &HashMap<Symbol, String>,
+    ) -> Dictionary {
+        let mut fields = dict.fields.clone();
+        for value in fields.values_mut() {
+            *value = Self::rename_vars_in_term(value, bindings, var_aliases);
+        }
+        Dictionary { fields }
+    }
+
+    /// `rename_vars_in_term` for a specializer `Pattern`: recurses into an instance/dict
+    /// pattern's fields, or each operand of a `Logical` (`and`/`or`/`not`) combinator.
+    fn rename_vars_in_pattern(
+        pattern: &Pattern,
+        bindings: &HashMap<String, Term>,
+        var_aliases: &HashMap<Symbol, String>,
+    ) -> Pattern {
+        match pattern {
+            Pattern::Instance(InstanceLiteral { tag, fields }) => Pattern::Instance(InstanceLiteral {
+                tag: tag.clone(),
+                fields: Self::rename_vars_in_dictionary(fields, bindings, var_aliases),
+            }),
+            Pattern::Dictionary(fields) => {
+                Pattern::Dictionary(Self::rename_vars_in_dictionary(fields, bindings, var_aliases))
+            }
+            Pattern::Logical(op, operands) => Pattern::Logical(
+                op.clone(),
+                operands
+                    .iter()
+                    .map(|operand| Self::rename_vars_in_pattern(operand, bindings, var_aliases))
+                    .collect(),
+            ),
+        }
     }
 
     /// Define a constant variable.
@@ -602,9 +1963,10 @@ impl KnowledgeBase {
         Ok(())
     }
 
-    /// Return true if a constant with the given name has been defined.
+    /// Return true if a constant with the given name has been defined, resolving through the
+    /// alias table first.
     pub fn is_constant(&self, name: &Symbol) -> bool {
-        self.constants.contains_key(name)
+        self.constants.contains_key(self.resolve_alias(name))
     }
 
     /// Getter for `constants` map without exposing it for mutation.
@@ -612,16 +1974,44 @@ impl KnowledgeBase {
         &self.constants
     }
 
+    /// Register `alias` as an additional name for the already-registered class `canonical`, so
+    /// that policies may specialize on either name.
+    ///
+    /// Errors if `canonical` isn't a registered constant, or if `alias` collides with an existing
+    /// constant or alias.
+    pub fn register_class_alias(&mut self, alias: Symbol, canonical: Symbol) -> PolarResult<()> {
+        if !self.constants.contains_key(&canonical) {
+            let msg = format!(
+                "Cannot alias {} to {}: {} is not a registered class.",
+                alias, canonical, canonical
+            );
+            return Err(error::OperationalError::InvalidState { msg }.into());
+        }
+        if self.constants.contains_key(&alias) || self.aliases.contains_key(&alias) {
+            let msg = format!(
+                "Cannot register alias {}: a class or alias with that name already exists.",
+                alias
+            );
+            return Err(error::OperationalError::InvalidState { msg }.into());
+        }
+        self.aliases.insert(alias, canonical);
+        Ok(())
+    }
+
+    /// Resolve `name` to the canonical class name it refers to, following at most one alias hop.
+    fn resolve_alias<'a>(&'a self, name: &'a Symbol) -> &'a Symbol {
+        self.aliases.get(name).unwrap_or(name)
+    }
+
     // TODO(gj): currently no way to distinguish classes from other registered constants in the
     // core, so it's up to callers to ensure this is only called with terms we expect to be
     // registered as a _class_.
     pub fn get_registered_class(&self, class: &Term) -> PolarResult<&Term> {
-        self.constants
-            .get(class.value().as_symbol()?)
-            .ok_or_else(|| {
-                let term = class.clone();
-                ValidationError::UnregisteredClass { term }.into()
-            })
+        let name = class.value().as_symbol()?;
+        self.constants.get(self.resolve_alias(name)).ok_or_else(|| {
+            let term = class.clone();
+            ValidationError::UnregisteredClass { term }.into()
+        })
     }
 
     /// Add the Method Resolution Order (MRO) list for a registered class.
@@ -636,6 +2026,17 @@ impl KnowledgeBase {
         Ok(())
     }
 
+    /// Copy `other`'s registered constants, class aliases, and MROs into `self`, leaving `self`'s
+    /// rules, rule types, resource blocks, and sources untouched. Used by `Polar::reload` to carry
+    /// class registrations forward into the fresh `KnowledgeBase` built for each reload attempt,
+    /// since those registrations come from the host language and aren't part of the policy text
+    /// being reloaded.
+    pub fn copy_registrations_from(&mut self, other: &KnowledgeBase) {
+        self.constants = other.constants.clone();
+        self.aliases = other.aliases.clone();
+        self.mro = other.mro.clone();
+    }
+
     pub fn add_source(&mut self, source: Source) -> PolarResult<u64> {
         let src_id = self.new_id();
         if let Some(ref filename) = source.filename {
@@ -650,6 +2051,7 @@ impl KnowledgeBase {
 
     pub fn clear_rules(&mut self) {
         self.rules.clear();
+        self.rules_by_source.clear();
         self.rule_types.reset();
         self.sources = Sources::default();
         self.inline_queries.clear();
@@ -658,6 +2060,54 @@ impl KnowledgeBase {
         self.resource_blocks.clear();
     }
 
+    /// Remove only the rules and inline queries that came from the previous load of `filename`,
+    /// leaving rules from other files, registered constants, and MROs untouched. Returns the
+    /// freed-up filename's old `src_id`, if it had been loaded before.
+    ///
+    /// Callers reload a file by calling this, then parsing the new contents against a fresh
+    /// `add_source` call and `add_rule`-ing the results back in, so a single frequently-edited
+    /// file doesn't force throwing away the whole knowledge base.
+    ///
+    /// TODO(gj): rule types and resource blocks derived from `filename` aren't tracked by source
+    /// yet, so a reload that changes either will leave stale entries behind; re-declaring them is
+    /// currently the caller's responsibility.
+    pub fn remove_source(&mut self, filename: &str) -> PolarResult<Option<u64>> {
+        let old_src_id = match self.loaded_files.remove(filename) {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+
+        let affected_names: HashSet<Symbol> = self
+            .rules_by_source
+            .remove(&old_src_id)
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        for name in affected_names {
+            if let Some(generic_rule) = self.rules.remove(&name) {
+                for rule in generic_rule.rules.values() {
+                    if rule.body.get_source_id() != Some(old_src_id) {
+                        self.add_rule((**rule).clone());
+                    }
+                }
+            }
+        }
+
+        self.inline_queries
+            .retain(|term| term.get_source_id() != Some(old_src_id));
+
+        if let Some(stale_content) = self
+            .loaded_content
+            .iter()
+            .find(|(_, f)| f.as_str() == filename)
+            .map(|(content, _)| content.clone())
+        {
+            self.loaded_content.remove(&stale_content);
+        }
+
+        Ok(Some(old_src_id))
+    }
+
     fn check_file(&self, src: &str, filename: &str) -> PolarResult<()> {
         match (
             self.loaded_content.get(src),
@@ -891,9 +2341,324 @@ impl KnowledgeBase {
         }
     }
 
+    /// Recursively resolve the set of concrete (non-union) member symbols reachable from
+    /// `union`, expanding any member that is itself a union. Guards against cyclic union
+    /// references and memoizes the result per union symbol.
+    fn resolve_union_members(&self, union: &Symbol) -> HashSet<Symbol> {
+        if let Some(cached) = self.union_membership_cache.borrow().get(union) {
+            return cached.clone();
+        }
+        let mut visited = HashSet::new();
+        let resolved = self.resolve_union_members_rec(union, &mut visited);
+        self.union_membership_cache
+            .borrow_mut()
+            .insert(union.clone(), resolved.clone());
+        resolved
+    }
+
+    fn resolve_union_members_rec(&self, union: &Symbol, visited: &mut HashSet<Symbol>) -> HashSet<Symbol> {
+        if !visited.insert(union.clone()) {
+            return HashSet::new();
+        }
+        self.get_union_members(&term!(union.clone()))
+            .iter()
+            .filter_map(|member| member.value().as_symbol().ok().cloned())
+            .flat_map(|member| {
+                if self.is_union(&term!(member.clone())) {
+                    self.resolve_union_members_rec(&member, visited)
+                } else {
+                    HashSet::from([member])
+                }
+            })
+            .collect()
+    }
+
+    /// Declaration-kind hint nudging a policy author toward the block type that would make
+    /// `missing_member` a member of `union_name`, e.g. suggesting an `actor { .. }` block.
+    /// Returns an empty string for unions with no known hint (e.g. user-declared unions).
+    fn union_declaration_hint(union_name: &Symbol, missing_member: &Symbol) -> String {
+        match union_name.0.as_str() {
+            ACTOR_UNION_NAME => format!(
+                "\n\n\tPerhaps you meant to add an actor block to the top of your policy, like this:\n\n\t  actor {} {{}}",
+                missing_member
+            ),
+            RESOURCE_UNION_NAME => format!(
+                "\n\n\tPerhaps you meant to add a resource block to your policy, like this:\n\n\t  resource {} {{ .. }}",
+                missing_member
+            ),
+            _ => String::new(),
+        }
+    }
+
     pub fn has_rules(&self) -> bool {
         !self.rules.is_empty()
     }
+
+    /// "Did you mean `X`?" support: find the declared rule name closest to `name` by Levenshtein
+    /// distance, for surfacing alongside a `MissingRequiredRule` diagnostic so a typo'd helper
+    /// rule (e.g. `has_relaton` instead of `has_relation`) gets an actionable hint instead of a
+    /// bare "rule not found". Returns `None` if nothing declared is within
+    /// `MAX_SUGGESTION_DISTANCE` edits of `name`.
+    pub fn suggest_rule_name(&self, name: &str) -> Option<String> {
+        Self::closest_match(name, self.rules.keys().map(|sym| sym.0.as_str()))
+    }
+
+    /// Record `suggest_rule_name`'s answer for `missing_rule` into `missing_rule_suggestions`,
+    /// called from `validate_rule_types_scoped` while `self.rules` still reflects the load that
+    /// produced the `MissingRequiredRule` diagnostic for it -- see the field's doc comment for why
+    /// this can't just be recomputed later.
+    fn cache_missing_rule_suggestion(&self, missing_rule: &Symbol) {
+        if let Some(suggestion) = self.suggest_rule_name(&missing_rule.0) {
+            self.missing_rule_suggestions
+                .borrow_mut()
+                .insert(missing_rule.clone(), suggestion);
+        }
+    }
+
+    /// Look up the "did you mean" hint cached by `cache_missing_rule_suggestion` for a
+    /// `MissingRequiredRule` diagnostic naming `missing_rule`, if the last full validation pass
+    /// found one.
+    pub fn missing_rule_suggestion(&self, missing_rule: &Symbol) -> Option<String> {
+        self.missing_rule_suggestions
+            .borrow()
+            .get(missing_rule)
+            .cloned()
+    }
+
+    /// Queue the source ID of `shadowed_by`'s body onto `related_diagnostic_sources`, keyed by
+    /// `flagged`'s own rendered Polar text, so the file it came from can still be named once the
+    /// diagnostic this produced has lost direct access to either rule. Queued rather than simply
+    /// inserted, since `flagged`'s text isn't unique -- two unrelated flagged rules (e.g. from two
+    /// different files) can render to the same text, and each occurrence needs its own source ID
+    /// preserved rather than the later one stomping the earlier one. No-op if `shadowed_by` has no
+    /// source ID (e.g. a rule built directly via the `rule!` test macro rather than parsed from a
+    /// file).
+    fn cache_related_diagnostic_source(&self, flagged: &str, shadowed_by: &Term) {
+        if let Some(source_id) = shadowed_by.get_source_id() {
+            self.related_diagnostic_sources
+                .borrow_mut()
+                .entry(flagged.to_string())
+                .or_default()
+                .push_back(source_id);
+        }
+    }
+
+    /// Look up the filename of the secondary location cached by `cache_related_diagnostic_source`
+    /// for the `RedundantRule`/`RedundantRuleType` diagnostic whose own rendered text is
+    /// `flagged_text` (i.e. that diagnostic's `rule`/`rule_type` field). `None` if nothing was
+    /// cached, or the source that was cached has no filename (e.g. an inline/anonymous source).
+    ///
+    /// Dequeues (FIFO) one entry per call rather than peeking, so that looking up the same
+    /// `flagged_text` more than once -- as happens when several independent flagged rules render
+    /// to identical text -- returns each occurrence's own source instead of the same one
+    /// repeatedly. See the `related_diagnostic_sources` field doc for the ordering assumption this
+    /// relies on.
+    ///
+    /// This only ever resolves to a filename, not a line/column within it: as documented on
+    /// `SourceRange`, this snapshot has no `Term` byte-offset accessor to derive a precise span
+    /// from, so file-level is as fine-grained as a secondary label can get here.
+    pub fn related_diagnostic_source_file(&self, flagged_text: &str) -> Option<String> {
+        let mut sources = self.related_diagnostic_sources.borrow_mut();
+        let queue = sources.get_mut(flagged_text)?;
+        let source_id = queue.pop_front()?;
+        if queue.is_empty() {
+            sources.remove(flagged_text);
+        }
+        drop(sources);
+        self.sources.get_source(source_id)?.filename
+    }
+
+    /// "Did you mean `X`?" support: find the relation/role/permission name declared across this
+    /// KB's resource blocks closest to `name` by Levenshtein distance. Relation names come off
+    /// `export_relation_graph`'s `TypedRelation`s -- the only place this file can read declared
+    /// relation names back from, since `ResourceBlocks`' own per-resource role/permission
+    /// bookkeeping isn't exposed past that graph.
+    ///
+    /// Not yet wired into a live diagnostic: an undeclared role/permission name referenced by a
+    /// shorthand rule (e.g. `"writer" if "adimn" on "parent"`) is currently rejected by
+    /// `ResourceBlock::as_rule`, which this file doesn't define, so there's no call site here that
+    /// sees that failure to attach a suggestion to. Exposed as its own method so that call site
+    /// can reach for it once it does.
+    pub fn suggest_resource_block_name(&self, name: &str) -> Option<String> {
+        let graph = self.export_relation_graph();
+        Self::closest_match(
+            name,
+            graph.relations.iter().map(|r| r.relation_name.as_str()),
+        )
+    }
+
+    /// Find the candidate closest to `name` by Levenshtein distance, for "did you mean" hints.
+    /// Returns `None` if nothing is within `MAX_SUGGESTION_DISTANCE` edits, or if `candidates` is
+    /// empty.
+    fn closest_match<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+        const MAX_SUGGESTION_DISTANCE: usize = 2;
+        candidates
+            .filter(|candidate| *candidate != name)
+            .map(|candidate| (candidate, Self::levenshtein_distance(name, candidate)))
+            .filter(|(_, distance)| *distance <= MAX_SUGGESTION_DISTANCE)
+            .min_by_key(|(_, distance)| *distance)
+            .map(|(candidate, _)| candidate)
+    }
+
+    /// Classic dynamic-programming edit distance between two strings (single-row rolling
+    /// buffer), used to power `closest_match`.
+    fn levenshtein_distance(a: &str, b: &str) -> usize {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        let mut row: Vec<usize> = (0..=b.len()).collect();
+        for (i, &a_char) in a.iter().enumerate() {
+            let mut prev_diag = row[0];
+            row[0] = i + 1;
+            for (j, &b_char) in b.iter().enumerate() {
+                let cur = row[j + 1];
+                row[j + 1] = if a_char == b_char {
+                    prev_diag
+                } else {
+                    1 + prev_diag.min(row[j]).min(row[j + 1])
+                };
+                prev_diag = cur;
+            }
+        }
+        row[b.len()]
+    }
+
+    /// Walk the resource blocks loaded into this KB and emit a structured description of the
+    /// authorization model they declare: every actor/resource type and the typed relations
+    /// between them, tagged with whether the relation is required (i.e. traversed by a shorthand
+    /// rule) or merely declared.
+    ///
+    /// Call after `create_resource_specific_rule_types` has run (as `Polar::diagnostic_load`
+    /// always does) so required-ness can be read back off the generated `has_relation`/`has_role`
+    /// rule types rather than re-deriving it from the resource blocks.
+    pub fn export_relation_graph(&self) -> RelationGraph {
+        let nodes = self
+            .resource_blocks
+            .actors
+            .iter()
+            .filter_map(|term| term.value().as_symbol().ok())
+            .map(|sym| RelationGraphNode {
+                name: sym.0.clone(),
+                kind: RelationGraphNodeKind::Actor,
+            })
+            .chain(
+                self.resource_blocks
+                    .resources
+                    .iter()
+                    .filter_map(|term| term.value().as_symbol().ok())
+                    .map(|sym| RelationGraphNode {
+                        name: sym.0.clone(),
+                        kind: RelationGraphNodeKind::Resource,
+                    }),
+            )
+            .collect();
+
+        let relations = self
+            .resource_blocks
+            .relation_tuples()
+            .into_iter()
+            .filter_map(|(subject, name, object)| {
+                let subject_type = subject.value().as_symbol().ok()?.0.clone();
+                let object_type = object.value().as_symbol().ok()?.0.clone();
+                let relation_name = name.value().as_string().ok()?.to_owned();
+                let required = self.relation_is_required(subject, name, object);
+                Some(TypedRelation {
+                    subject_type,
+                    relation_name,
+                    object_type,
+                    required,
+                })
+            })
+            .collect();
+
+        RelationGraph {
+            nodes,
+            relations,
+            has_roles: self.resource_blocks.has_roles(),
+        }
+    }
+
+    /// True if `(subject, name, object)` corresponds to one of the `has_relation` rule types
+    /// `create_resource_specific_rule_types` marked required -- i.e. a shorthand rule actually
+    /// traverses this relation, as opposed to it only being declared.
+    fn relation_is_required(&self, subject: &Term, name: &Term, object: &Term) -> bool {
+        self.rule_types.required_rule_types().iter().any(|rt| {
+            rt.name.0 == "has_relation"
+                && rt.params.len() == 3
+                && Self::param_instance_tag(&rt.params[0]) == subject.value().as_symbol().ok()
+                && rt.params[1].parameter.value().as_string().ok() == name.value().as_string().ok()
+                && Self::param_instance_tag(&rt.params[2]) == object.value().as_symbol().ok()
+        })
+    }
+
+    /// Extract the `InstanceLiteral` tag from a parameter's pattern specializer, if it has one.
+    fn param_instance_tag(param: &Parameter) -> Option<&Symbol> {
+        match param.specializer.as_ref().map(Term::value) {
+            Some(Value::Pattern(Pattern::Instance(InstanceLiteral { tag, .. }))) => Some(tag),
+            _ => None,
+        }
+    }
+}
+
+/// A node in an [`RelationGraph`]: an actor or resource type declared by a resource block.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RelationGraphNode {
+    pub name: String,
+    pub kind: RelationGraphNodeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum RelationGraphNodeKind {
+    Actor,
+    Resource,
+}
+
+/// A typed relation between two resource-block types, e.g. `Repo --parent_org--> Org`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TypedRelation {
+    pub subject_type: String,
+    pub relation_name: String,
+    pub object_type: String,
+    /// True if some shorthand rule actually traverses this relation (as opposed to it being
+    /// merely declared in a `relations = { .. }` block without being used).
+    pub required: bool,
+}
+
+/// The structured authorization-graph artifact produced by [`KnowledgeBase::export_relation_graph`].
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RelationGraph {
+    pub nodes: Vec<RelationGraphNode>,
+    pub relations: Vec<TypedRelation>,
+    pub has_roles: bool,
+}
+
+impl RelationGraph {
+    /// Render this graph as an s-expression, in the style selinux-cascade uses to lower its
+    /// high-level policy to CIL, e.g. `(relation Repo parent_org Org)`.
+    pub fn to_sexp(&self) -> String {
+        let mut lines = vec![];
+        for node in &self.nodes {
+            let kind = match node.kind {
+                RelationGraphNodeKind::Actor => "actor",
+                RelationGraphNodeKind::Resource => "resource",
+            };
+            lines.push(format!("({} {})", kind, node.name));
+        }
+        for relation in &self.relations {
+            let tag = if relation.required {
+                "relation"
+            } else {
+                "declared-relation"
+            };
+            lines.push(format!(
+                "({} {} {} {})",
+                tag, relation.subject_type, relation.relation_name, relation.object_type
+            ));
+        }
+        if self.has_roles {
+            lines.push("(has-roles)".to_owned());
+        }
+        lines.join("\n")
+    }
 }
 
 #[cfg(test)]
@@ -1411,14 +3176,706 @@ mod tests {
     }
 
     #[test]
-    fn test_validate_rules() {
+    fn test_ssr_search_and_rewrite_rules() {
         let mut kb = KnowledgeBase::new();
-        kb.register_constant(
-            sym!("Fruit"),
-            term!(Value::ExternalInstance(ExternalInstance {
-                instance_id: 1,
-                constructor: None,
-                repr: None
+        kb.add_rule(rule!("role_allow", [sym!("actor"), sym!("resource")]));
+        kb.add_rule(rule!("role_allow", [sym!("a"), sym!("r")]));
+        kb.add_rule(rule!("other", [sym!("x")]));
+
+        let matches = kb.search_rules("role_allow($a, $r)").unwrap();
+        assert_eq!(matches.len(), 2);
+
+        let rewritten = kb
+            .rewrite_rules("role_allow($a, $r)", "has_role($a, $r)")
+            .unwrap();
+        assert_eq!(rewritten, 2);
+
+        assert!(kb.get_generic_rule(&sym!("role_allow")).is_none());
+        assert_eq!(
+            kb.get_generic_rule(&sym!("has_role"))
+                .unwrap()
+                .rules
+                .len(),
+            2
+        );
+        // The unrelated rule is untouched.
+        assert!(kb.get_generic_rule(&sym!("other")).is_some());
+    }
+
+    #[test]
+    fn test_ssr_typed_metavar_and_plan_rewrite() {
+        let mut kb = KnowledgeBase::new();
+        kb.register_constant(
+            sym!("Document"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 1,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        kb.add_mro(sym!("Document"), vec![1]).unwrap();
+        kb.register_constant(
+            sym!("Folder"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 2,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        kb.add_mro(sym!("Folder"), vec![2]).unwrap();
+
+        kb.add_rule(rule!(
+            "allow",
+            [sym!("actor"), value!("read"), "resource"; instance!(sym!("Document"))]
+        ));
+        kb.add_rule(rule!(
+            "allow",
+            [sym!("actor"), value!("read"), "resource"; instance!(sym!("Folder"))]
+        ));
+
+        // `$resource: Document` only matches the rule specializing on Document.
+        let matches = kb
+            .search_rules("allow($actor, \"read\", $resource: Document)")
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+
+        // `plan_rewrite` previews the edit without mutating the KB; the matched rule's original
+        // specializer is preserved since the replacement template leaves that position untyped.
+        let plan = kb
+            .plan_rewrite(
+                "allow($actor, \"read\", $resource: Document)",
+                "can_read($actor, $resource)",
+            )
+            .unwrap();
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].0.name, sym!("can_read"));
+        assert!(plan[0].1.contains("Document"));
+        assert!(kb.get_generic_rule(&sym!("can_read")).is_none());
+        assert_eq!(
+            kb.get_generic_rule(&sym!("allow")).unwrap().rules.len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_ssr_search_by_body_call() {
+        let mut kb = KnowledgeBase::new();
+        let mut allow_rule = rule!("allow", [sym!("actor"), sym!("action"), sym!("resource")]);
+        allow_rule.body = term!(Value::Expression(Operation {
+            operator: Operator::And,
+            args: vec![term!(Value::Call(Call {
+                name: sym!("is_public"),
+                args: vec![term!(sym!("resource"))],
+                kwargs: None,
+            }))],
+        }));
+        kb.add_rule(allow_rule);
+        kb.add_rule(rule!("other", [sym!("x")]));
+
+        // Neither rule's head matches, but `allow`'s body has an `is_public(resource)` conjunct.
+        let matches = kb.search_rules("is_public($resource)").unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].name, sym!("allow"));
+
+        // A typed metavariable has no specializer to check against in a plain call argument.
+        assert!(kb
+            .search_rules("is_public($resource: Document)")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_partition_equivs_collapses_unify_chain_to_one_representative() {
+        let unify = |l: Term, r: Term| {
+            term!(Value::Expression(Operation {
+                operator: Operator::Unify,
+                args: vec![l, r],
+            }))
+        };
+        let conjuncts = vec![
+            unify(term!(sym!("x")), term!(sym!("y"))),
+            unify(term!(sym!("y")), term!(sym!("z"))),
+            // Not a variable-to-variable unification -- ignored.
+            unify(term!(sym!("w")), term!(1)),
+        ];
+
+        let classes = partition_equivs(&conjuncts);
+        let rep = classes[&sym!("x")].clone();
+        assert_eq!(classes[&sym!("y")], rep);
+        assert_eq!(classes[&sym!("z")], rep);
+        assert!(!classes.contains_key(&sym!("w")));
+    }
+
+    #[test]
+    fn test_rename_vars_in_term_recurses_into_dictionary_and_pattern() {
+        let mut bindings = HashMap::new();
+        bindings.insert("resource".to_owned(), term!(sym!("renamed")));
+        let mut var_aliases = HashMap::new();
+        var_aliases.insert(sym!("r"), "resource".to_owned());
+
+        // A metavar-bound variable nested inside a dict literal (not Expression/List) is found
+        // and renamed.
+        let dict_term = term!(Value::Dictionary(Dictionary {
+            fields: btreemap! { sym!("key") => term!(sym!("r")) },
+        }));
+        let renamed = KnowledgeBase::rename_vars_in_term(&dict_term, &bindings, &var_aliases);
+        match renamed.value() {
+            Value::Dictionary(Dictionary { fields }) => {
+                assert_eq!(fields.get(&sym!("key")), Some(&term!(sym!("renamed"))));
+            }
+            other => panic!("expected a Dictionary, got {:?}", other),
+        }
+
+        // Same, nested inside an instance-pattern specializer's fields.
+        let pattern_term = term!(Value::Pattern(Pattern::Instance(InstanceLiteral {
+            tag: sym!("Foo"),
+            fields: Dictionary {
+                fields: btreemap! { sym!("key") => term!(sym!("r")) },
+            },
+        })));
+        let renamed = KnowledgeBase::rename_vars_in_term(&pattern_term, &bindings, &var_aliases);
+        match renamed.value() {
+            Value::Pattern(Pattern::Instance(InstanceLiteral { fields, .. })) => {
+                assert_eq!(fields.fields.get(&sym!("key")), Some(&term!(sym!("renamed"))));
+            }
+            other => panic!("expected an instance Pattern, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_rule_params_match_collects_all_failures() {
+        let kb = KnowledgeBase::new();
+
+        // Both parameters mismatch; both should be reported rather than just the first.
+        match kb
+            .rule_params_match(
+                &rule!("f", [value!(1), value!("a")]),
+                &rule!("f", [value!(2), value!("b")]),
+            )
+            .unwrap()
+        {
+            RuleParamMatch::Failures(failures) => {
+                assert_eq!(failures.len(), 2);
+                assert_eq!(failures[0].0, 1);
+                assert_eq!(failures[1].0, 2);
+            }
+            _ => panic!("expected RuleParamMatch::Failures"),
+        }
+    }
+
+    #[test]
+    fn test_rule_params_match_reasons() {
+        let mut kb = KnowledgeBase::new();
+        kb.register_constant(
+            sym!("Fruit"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 1,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        kb.add_mro(sym!("Fruit"), vec![1]).unwrap();
+        kb.register_constant(
+            sym!("Veggie"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 2,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        kb.add_mro(sym!("Veggie"), vec![2]).unwrap();
+
+        // Arity mismatch.
+        assert_eq!(
+            kb.rule_params_match_reasons(&rule!("f", [sym!("x")]), &rule!("f", [sym!("x"), sym!("y")]))
+                .unwrap(),
+            vec![MatchFailureReason::ArityMismatch { expected: 2, got: 1 }]
+        );
+
+        // Rule specializes on a class that isn't a subclass of the rule type's class.
+        assert_eq!(
+            kb.rule_params_match_reasons(
+                &rule!("f", ["x"; instance!(sym!("Veggie"))]),
+                &rule!("f", ["x"; instance!(sym!("Fruit"))]),
+            )
+            .unwrap(),
+            vec![MatchFailureReason::NotASubclass {
+                param_index: 1,
+                class: "Veggie".to_owned(),
+                required: "Fruit".to_owned(),
+            }]
+        );
+
+        // A match produces no reasons.
+        assert!(kb
+            .rule_params_match_reasons(
+                &rule!("f", ["x"; instance!(sym!("Fruit"))]),
+                &rule!("f", ["x"; instance!(sym!("Fruit"))]),
+            )
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_rule_params_match_equality_binding_wildcard() {
+        let mut kb = KnowledgeBase::new();
+        kb.register_constant(
+            sym!("Fruit"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 1,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        kb.add_mro(sym!("Fruit"), vec![1]).unwrap();
+        kb.register_constant(
+            sym!("Citrus"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 2,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        // Citrus is a subclass of Fruit.
+        kb.add_mro(sym!("Citrus"), vec![2, 1]).unwrap();
+        kb.register_constant(
+            sym!("Veggie"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 3,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        kb.add_mro(sym!("Veggie"), vec![3]).unwrap();
+
+        // f(a: $T, b: $T) matches a rule whose two parameters share the same concrete class...
+        assert!(kb
+            .rule_params_match(
+                &rule!(
+                    "f",
+                    ["a"; instance!(sym!("Fruit")), "b"; instance!(sym!("Fruit"))]
+                ),
+                &rule!("f", ["a"; instance!(sym!("$T")), "b"; instance!(sym!("$T"))]),
+            )
+            .unwrap()
+            .is_true());
+
+        // ...or where the second parameter is a subclass of the class the first bound $T to.
+        assert!(kb
+            .rule_params_match(
+                &rule!(
+                    "f",
+                    ["a"; instance!(sym!("Fruit")), "b"; instance!(sym!("Citrus"))]
+                ),
+                &rule!("f", ["a"; instance!(sym!("$T")), "b"; instance!(sym!("$T"))]),
+            )
+            .unwrap()
+            .is_true());
+
+        // But not when the second parameter's class is unrelated to the one $T already bound to.
+        assert!(!kb
+            .rule_params_match(
+                &rule!(
+                    "f",
+                    ["a"; instance!(sym!("Fruit")), "b"; instance!(sym!("Veggie"))]
+                ),
+                &rule!("f", ["a"; instance!(sym!("$T")), "b"; instance!(sym!("$T"))]),
+            )
+            .unwrap()
+            .is_true());
+    }
+
+    #[test]
+    fn test_register_class_alias() {
+        let mut kb = KnowledgeBase::new();
+        kb.register_constant(
+            sym!("Repo"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 1,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        kb.add_mro(sym!("Repo"), vec![1]).unwrap();
+
+        // Can't alias to an unregistered class.
+        assert!(kb.register_class_alias(sym!("OldRepo"), sym!("Nonexistent")).is_err());
+
+        kb.register_class_alias(sym!("OldRepo"), sym!("Repo")).unwrap();
+        assert!(kb.is_constant(&sym!("OldRepo")));
+        assert!(kb.get_registered_class(&term!(sym!("OldRepo"))).is_ok());
+
+        // Can't alias over an existing constant or alias.
+        assert!(kb.register_class_alias(sym!("Repo"), sym!("Repo")).is_err());
+        assert!(kb.register_class_alias(sym!("OldRepo"), sym!("Repo")).is_err());
+
+        // A rule specializing on the alias matches a rule type specializing on the canonical name.
+        assert!(kb
+            .rule_params_match(
+                &rule!("f", ["x"; instance!(sym!("OldRepo"))]),
+                &rule!("f", ["x"; instance!(sym!("Repo"))])
+            )
+            .unwrap()
+            .is_true());
+    }
+
+    #[test]
+    fn test_check_redundant_rules() {
+        let mut kb = KnowledgeBase::new();
+        kb.register_constant(
+            sym!("Fruit"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 1,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        kb.add_mro(sym!("Fruit"), vec![1]).unwrap();
+
+        // A later, identical rule is redundant -- it adds no solution the earlier one doesn't.
+        kb.add_rule(rule!("f", ["x"; instance!(sym!("Fruit"))]));
+        kb.add_rule(rule!("f", ["x"; instance!(sym!("Fruit"))]));
+        assert_eq!(kb.check_redundant_rules().len(), 1);
+
+        // A rule with a distinct, unrelated name is never redundant.
+        kb.clear_rules();
+        kb.add_rule(rule!("f", ["x"; instance!(sym!("Fruit"))]));
+        kb.add_rule(rule!("g", ["x"; instance!(sym!("Fruit"))]));
+        assert!(kb.check_redundant_rules().is_empty());
+
+        // Two rules with no specializer but different value patterns (`f(1); f(2);`) match
+        // disjoint inputs and must never be flagged, even though neither has a specializer to
+        // compare -- Polar evaluates every matching clause via backtracking, so a later rule with
+        // a distinct, non-overlapping head is never unreachable.
+        kb.clear_rules();
+        kb.add_rule(rule!("f", [value!(1)]));
+        kb.add_rule(rule!("f", [value!(2)]));
+        assert!(kb.check_redundant_rules().is_empty());
+
+        // A verbatim duplicate -- same head, same body -- is genuinely redundant: it can't
+        // contribute a solution the earlier rule doesn't already produce.
+        kb.clear_rules();
+        kb.add_rule(rule!("f", [value!(1)]));
+        kb.add_rule(rule!("f", [value!(1)]));
+        assert_eq!(kb.check_redundant_rules().len(), 1);
+    }
+
+    #[test]
+    fn test_related_diagnostic_source_file() {
+        let mut kb = KnowledgeBase::new();
+        let src_id = kb
+            .add_source(Source {
+                src: "f(1); f(1);".to_owned(),
+                filename: Some("policy.polar".to_owned()),
+            })
+            .unwrap();
+
+        // Nothing was ever cached for text that wasn't flagged as a duplicate.
+        assert_eq!(kb.related_diagnostic_source_file("f(1);"), None);
+
+        kb.related_diagnostic_sources
+            .borrow_mut()
+            .entry("f(1);".to_owned())
+            .or_default()
+            .push_back(src_id);
+        assert_eq!(
+            kb.related_diagnostic_source_file("f(1);"),
+            Some("policy.polar".to_owned())
+        );
+
+        // The entry is consumed on lookup, not left behind to be returned again.
+        assert_eq!(kb.related_diagnostic_source_file("f(1);"), None);
+    }
+
+    #[test]
+    fn test_related_diagnostic_source_file_keeps_independent_occurrences_of_the_same_text() {
+        let mut kb = KnowledgeBase::new();
+        let file_a = kb
+            .add_source(Source {
+                src: "f(1); f(1);".to_owned(),
+                filename: Some("a.polar".to_owned()),
+            })
+            .unwrap();
+        let file_b = kb
+            .add_source(Source {
+                src: "f(1); f(1);".to_owned(),
+                filename: Some("b.polar".to_owned()),
+            })
+            .unwrap();
+
+        // Two unrelated files happen to flag identically-rendered text ("f(1);"). Caching both
+        // occurrences must not let the second overwrite the first.
+        {
+            let mut sources = kb.related_diagnostic_sources.borrow_mut();
+            sources
+                .entry("f(1);".to_owned())
+                .or_default()
+                .push_back(file_a);
+            sources
+                .entry("f(1);".to_owned())
+                .or_default()
+                .push_back(file_b);
+        }
+
+        assert_eq!(
+            kb.related_diagnostic_source_file("f(1);"),
+            Some("a.polar".to_owned())
+        );
+        assert_eq!(
+            kb.related_diagnostic_source_file("f(1);"),
+            Some("b.polar".to_owned())
+        );
+        assert_eq!(kb.related_diagnostic_source_file("f(1);"), None);
+    }
+
+    #[test]
+    fn test_validate_resource_block_schema_flags_undeclared_actor_type() {
+        let mut kb = KnowledgeBase::new();
+        kb.resource_blocks.actors.insert(term!(sym!("User")));
+
+        // `allow`'s actor position specializes on `Org`, which was never declared with an
+        // `actor` block -- only `User` was -- so the schema check should flag it.
+        kb.add_rule(rule!(
+            "allow",
+            ["actor"; instance!(sym!("Org")), value!("read"), sym!("resource")]
+        ));
+        let diagnostics = kb.validate_resource_block_schema();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(matches!(
+            diagnostics.first().unwrap(),
+            Diagnostic::Error(PolarError {
+                kind: ErrorKind::Validation(ValidationError::UndeclaredActorType { .. }),
+                ..
+            })
+        ));
+
+        // Specializing on the declared actor type raises nothing.
+        kb.clear_rules();
+        kb.resource_blocks.actors.insert(term!(sym!("User")));
+        kb.add_rule(rule!(
+            "allow",
+            ["actor"; instance!(sym!("User")), value!("read"), sym!("resource")]
+        ));
+        assert!(kb.validate_resource_block_schema().is_empty());
+
+        // With no resource blocks declared at all, the check is skipped entirely -- there's no
+        // schema to check `allow`'s actor position against.
+        kb.clear_rules();
+        kb.add_rule(rule!(
+            "allow",
+            ["actor"; instance!(sym!("Org")), value!("read"), sym!("resource")]
+        ));
+        assert!(kb.validate_resource_block_schema().is_empty());
+    }
+
+    #[test]
+    fn test_validate_registered_resource_types() {
+        let mut kb = KnowledgeBase::new();
+        kb.resource_blocks.resources.insert(term!(sym!("Repo")));
+
+        // Declared with a `resource` block, but never registered with the host at all.
+        let diagnostics = kb.validate_registered_resource_types();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(matches!(
+            diagnostics.first().unwrap(),
+            Diagnostic::Error(PolarError {
+                kind: ErrorKind::Validation(ValidationError::UnregisteredResourceType { .. }),
+                ..
+            })
+        ));
+
+        // Registered as a constant, but the host never called `add_mro` for it either.
+        kb.register_constant(
+            sym!("Repo"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 1,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        assert_eq!(kb.validate_registered_resource_types().len(), 1);
+
+        // Fully registered -- nothing to report.
+        kb.add_mro(sym!("Repo"), vec![1]).unwrap();
+        assert!(kb.validate_registered_resource_types().is_empty());
+    }
+
+    #[test]
+    fn test_check_redundant_rule_types() {
+        let mut kb = KnowledgeBase::new();
+        kb.register_constant(
+            sym!("Fruit"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 1,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        kb.add_mro(sym!("Fruit"), vec![1]).unwrap();
+        kb.register_constant(
+            sym!("Orange"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 2,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        kb.add_mro(sym!("Orange"), vec![2, 1]).unwrap();
+
+        // A rule must exist with this name for its rule types to be considered.
+        kb.add_rule(rule!("f", ["x"; instance!(sym!("Orange"))]));
+
+        // The second template only matches a subset of what the first already matches, so it's
+        // redundant -- anything matching Orange already matches Fruit.
+        kb.add_rule_type(rule!("f", ["x"; instance!(sym!("Fruit"))]));
+        kb.add_rule_type(rule!("f", ["x"; instance!(sym!("Orange"))]));
+        assert_eq!(kb.check_redundant_rule_types().len(), 1);
+
+        // A class template and an unrelated value template don't subsume each other.
+        kb.rule_types.reset();
+        kb.add_rule_type(rule!("f", ["x"; instance!(sym!("Orange"))]));
+        kb.add_rule_type(rule!("f", [value!(1)]));
+        assert!(kb.check_redundant_rule_types().is_empty());
+    }
+
+    #[test]
+    fn test_check_redundant_rule_types_flags_specific_declared_before_general() {
+        let mut kb = KnowledgeBase::new();
+        kb.register_constant(
+            sym!("Fruit"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 1,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        kb.add_mro(sym!("Fruit"), vec![1]).unwrap();
+        kb.register_constant(
+            sym!("Orange"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 2,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        kb.add_mro(sym!("Orange"), vec![2, 1]).unwrap();
+
+        kb.add_rule(rule!("f", ["x"; instance!(sym!("Orange"))]));
+
+        // Same pair as `test_check_redundant_rule_types`, but declared in the opposite order --
+        // the specific (`Orange`) template first, the general (`Fruit`) one second. Subsumption
+        // doesn't care about declaration order, so this must still be flagged.
+        kb.add_rule_type(rule!("f", ["x"; instance!(sym!("Orange"))]));
+        kb.add_rule_type(rule!("f", ["x"; instance!(sym!("Fruit"))]));
+        assert_eq!(kb.check_redundant_rule_types().len(), 1);
+    }
+
+    #[test]
+    fn test_flatten_nested_patterns() {
+        let mut kb = KnowledgeBase::new();
+
+        // f(x: Foo{bar: Bar{baz: 1}}) should be flattened to a depth-1 specializer on x plus an
+        // `Isa` conjunct checking the hoisted variable against the nested `Bar` pattern.
+        kb.add_rule(rule!(
+            "f",
+            ["x"; instance!(sym!("Foo"), btreemap! { sym!("bar") => term!(instance!(sym!("Bar"), btreemap! { sym!("baz") => term!(1) })) })]
+        ));
+
+        let generic_rule = kb.get_generic_rule(&sym!("f")).unwrap();
+        let rule = generic_rule.rules.values().next().unwrap();
+
+        let Value::Pattern(Pattern::Instance(InstanceLiteral { fields, .. })) =
+            rule.params[0].specializer.as_ref().unwrap().value()
+        else {
+            panic!("expected an instance pattern specializer");
+        };
+        assert!(matches!(
+            fields.fields.get(&sym!("bar")).unwrap().value(),
+            Value::Variable(_)
+        ));
+        assert!(matches!(
+            rule.body.value(),
+            Value::Expression(Operation {
+                operator: Operator::And,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_unify_fields_recursive() {
+        let kb = KnowledgeBase::new();
+
+        // Nested dictionaries unify field-by-field rather than by identity.
+        let type_fields = Dictionary {
+            fields: btreemap! {
+                sym!("address") => term!(btreemap! { sym!("city") => term!("NYC") }),
+            },
+        };
+        let rule_fields_match = Dictionary {
+            fields: btreemap! {
+                sym!("address") => term!(btreemap! {
+                    sym!("city") => term!("NYC"),
+                    sym!("zip") => term!("10001"),
+                }),
+            },
+        };
+        assert!(kb.param_fields_match(&type_fields, &rule_fields_match));
+
+        let rule_fields_mismatch = Dictionary {
+            fields: btreemap! {
+                sym!("address") => term!(btreemap! { sym!("city") => term!("Boston") }),
+            },
+        };
+        assert!(!kb.param_fields_match(&type_fields, &rule_fields_mismatch));
+
+        // A repeated variable on the type side must agree across occurrences.
+        let repeated_var_type = Dictionary {
+            fields: btreemap! {
+                sym!("x") => term!(sym!("v")),
+                sym!("y") => term!(sym!("v")),
+            },
+        };
+        let agreeing_rule_fields = Dictionary {
+            fields: btreemap! {
+                sym!("x") => term!(1),
+                sym!("y") => term!(1),
+            },
+        };
+        assert!(kb.param_fields_match(&repeated_var_type, &agreeing_rule_fields));
+
+        let disagreeing_rule_fields = Dictionary {
+            fields: btreemap! {
+                sym!("x") => term!(1),
+                sym!("y") => term!(2),
+            },
+        };
+        assert!(!kb.param_fields_match(&repeated_var_type, &disagreeing_rule_fields));
+    }
+
+    #[test]
+    fn test_validate_rules() {
+        let mut kb = KnowledgeBase::new();
+        kb.register_constant(
+            sym!("Fruit"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 1,
+                constructor: None,
+                repr: None
             })),
         )
         .unwrap();
@@ -1486,4 +3943,104 @@ mod tests {
         kb.add_rule_type(rule!("f", ["x"; instance!(sym!("Fruit"))]));
         kb.add_rule(rule!("f", ["x"; instance!(sym!("Fruit"))]));
     }
+
+    #[test]
+    fn test_validate_rules_collects_every_missing_required_rule() {
+        let mut kb = KnowledgeBase::new();
+
+        // Two unrelated required rule types, neither implemented -- both should be reported in
+        // one pass instead of only the first.
+        kb.add_rule_type(rule!("has_relation", ["x"; value!(1)], true));
+        kb.add_rule_type(rule!("has_permission", ["x"; value!(1)], true));
+
+        let diagnostics = kb.validate_rules();
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|d| matches!(
+            d,
+            Diagnostic::Error(PolarError {
+                kind: ErrorKind::Validation(ValidationError::MissingRequiredRule { .. }),
+                ..
+            })
+        )));
+    }
+
+    #[test]
+    fn test_validate_rules_in_ranges() {
+        let mut kb = KnowledgeBase::new();
+        kb.register_constant(
+            sym!("Fruit"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 1,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        kb.register_constant(
+            sym!("Veggie"),
+            term!(Value::ExternalInstance(ExternalInstance {
+                instance_id: 2,
+                constructor: None,
+                repr: None
+            })),
+        )
+        .unwrap();
+        kb.add_mro(sym!("Fruit"), vec![1]).unwrap();
+        kb.add_mro(sym!("Veggie"), vec![2]).unwrap();
+
+        // Both "f" and "g" have rules that violate their rule type.
+        kb.add_rule_type(rule!("f", ["x"; instance!(sym!("Fruit"))]));
+        kb.add_rule(rule!("f", ["x"; instance!(sym!("Veggie"))]));
+        kb.add_rule_type(rule!("g", ["x"; instance!(sym!("Fruit"))]));
+        kb.add_rule(rule!("g", ["x"; instance!(sym!("Veggie"))]));
+
+        // Pretend "f" came from source 1 and "g" from source 2 (add_rule can't attach real
+        // source IDs here since these rules are built directly rather than parsed).
+        kb.rules_by_source.insert(1, vec![sym!("f")]);
+        kb.rules_by_source.insert(2, vec![sym!("g")]);
+
+        // Scoping to source 1's range only surfaces "f"'s violation.
+        let diagnostics = kb.validate_rules_in_ranges(&[SourceRange {
+            source_id: 1,
+            start: 0,
+            end: 0,
+        }]);
+        assert_eq!(diagnostics.len(), 1);
+        assert!(matches!(
+            diagnostics.first().unwrap(),
+            Diagnostic::Error(PolarError {
+                kind: ErrorKind::Validation(ValidationError::InvalidRule { .. }),
+                ..
+            })
+        ));
+
+        // An empty range set falls back to full-KB validation, catching both violations.
+        assert_eq!(kb.validate_rules_in_ranges(&[]).len(), 2);
+    }
+
+    #[test]
+    fn test_export_relation_graph() {
+        let mut kb = KnowledgeBase::new();
+        kb.resource_blocks.actors.insert(term!(sym!("User")));
+        kb.resource_blocks.resources.insert(term!(sym!("Repo")));
+        kb.resource_blocks.resources.insert(term!(sym!("Org")));
+
+        let graph = kb.export_relation_graph();
+        assert_eq!(graph.nodes.len(), 3);
+        assert!(graph
+            .nodes
+            .iter()
+            .any(|n| n.name == "User" && n.kind == RelationGraphNodeKind::Actor));
+        assert!(graph
+            .nodes
+            .iter()
+            .any(|n| n.name == "Repo" && n.kind == RelationGraphNodeKind::Resource));
+        assert!(graph.relations.is_empty());
+        assert!(!graph.has_roles);
+
+        let sexp = graph.to_sexp();
+        assert!(sexp.contains("(actor User)"));
+        assert!(sexp.contains("(resource Repo)"));
+        assert!(sexp.contains("(resource Org)"));
+    }
 }
@@ -18,25 +18,79 @@ use super::validations::{
 };
 use super::vm::*;
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// A cheaply-cloneable handle that lets an embedder abort an in-flight `Query` from another
+/// thread, or between `next_event` calls on the same thread -- e.g. because a request deadline
+/// upstream of the authorization check has already passed. Once tripped, the next `next_event`
+/// call returns `QueryEvent::Done` instead of continuing to run goals.
+#[derive(Debug, Clone, Default)]
+pub struct QueryCancelToken(Arc<AtomicBool>);
+
+impl QueryCancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
 
 pub struct Query {
     runnable_stack: Vec<(Box<dyn Runnable>, u64)>, // Tuple of Runnable + call_id.
     vm: PolarVirtualMachine,
     term: Term,
     done: bool,
+    deadline: Option<Instant>,
+    cancel_token: QueryCancelToken,
+    /// `PolarConfig::max_goals`, if set. Checked against `goals_executed` on every `next_event`
+    /// call. This counts top-level `Runnable::run` invocations (one per `next_event` stack frame,
+    /// including the recursive ones `recv_event` makes for `None`/`Run`/resumed-`Done` events) --
+    /// the finest granularity reachable from this file, since the VM's own internal goal stack
+    /// lives in `vm.rs`, outside this source snapshot. It's a coarser count than the VM's true
+    /// goal counter, but it's a real, enforced bound rather than a decorative config field.
+    max_goals: Option<u64>,
+    goals_executed: u64,
+    /// `PolarConfig::log_level`. `Debug`/`Trace` have `next_event`/`recv_event` emit a message
+    /// (via `vm.messages`) at, respectively, each runnable push/pop and each `next_event` step --
+    /// see `PolarConfig::log_level`'s doc comment for exactly what each level covers here.
+    log_level: LogLevel,
+    /// Unconditional `next_event`-step counter backing `Trace`-level messages. Kept separate from
+    /// `goals_executed` since that one only advances while `max_goals` is set.
+    steps_executed: u64,
 }
 
 impl Query {
-    pub fn new(vm: PolarVirtualMachine, term: Term) -> Self {
+    pub fn new(
+        vm: PolarVirtualMachine,
+        term: Term,
+        query_timeout: Option<Duration>,
+        max_goals: Option<u64>,
+        log_level: LogLevel,
+    ) -> Self {
         Self {
             runnable_stack: vec![],
             vm,
             term,
             done: false,
+            deadline: query_timeout.map(|timeout| Instant::now() + timeout),
+            cancel_token: QueryCancelToken::default(),
+            max_goals,
+            goals_executed: 0,
+            log_level,
+            steps_executed: 0,
         }
     }
 
+    /// A handle the embedder can use to cancel this query from another thread, or between
+    /// `next_event` calls. See `QueryCancelToken`.
+    pub fn cancel_token(&self) -> QueryCancelToken {
+        self.cancel_token.clone()
+    }
+
     #[cfg(target_arch = "wasm32")]
     pub fn set_logging_options(&mut self, rust_log: Option<String>, polar_log: Option<String>) {
         self.vm.set_logging_options(rust_log, polar_log);
@@ -50,6 +104,34 @@ impl Query {
     /// 4. When Runnable B emits a Done event, pop Runnable B off the stack and return its result as
     ///    an answer to Runnable A.
     pub fn next_event(&mut self) -> PolarResult<QueryEvent> {
+        if self.cancel_token.is_cancelled() {
+            return Ok(QueryEvent::Done { result: true });
+        }
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return Err(error::RuntimeError::QueryTimeout {
+                    msg: "Query exceeded its configured timeout".to_owned(),
+                }
+                .into());
+            }
+        }
+        if let Some(max_goals) = self.max_goals {
+            if self.goals_executed >= max_goals {
+                return Err(error::RuntimeError::ResourceLimitExceeded {
+                    msg: format!("Goal count exceeded! MAX_EXECUTED_GOALS = {max_goals}"),
+                }
+                .into());
+            }
+            self.goals_executed += 1;
+        }
+        self.steps_executed += 1;
+        if matches!(self.log_level, LogLevel::Trace) {
+            self.vm.messages.extend(std::iter::once(Message::warning(format!(
+                "[trace] step {} (runnable stack depth {})",
+                self.steps_executed,
+                self.runnable_stack.len()
+            ))));
+        }
         let mut counter = self.vm.id_counter();
         let qe = match self.top_runnable().run(Some(&mut counter)) {
             Ok(e) => e,
@@ -62,11 +144,21 @@ impl Query {
         match qe {
             QueryEvent::None => self.next_event(),
             QueryEvent::Run { runnable, call_id } => {
+                if matches!(self.log_level, LogLevel::Debug | LogLevel::Trace) {
+                    self.vm.messages.extend(std::iter::once(Message::warning(format!(
+                        "[debug] pushing runnable (call_id {call_id})"
+                    ))));
+                }
                 self.push_runnable(runnable, call_id);
                 self.next_event()
             }
             QueryEvent::Done { result } => {
                 if let Some((_, result_call_id)) = self.pop_runnable() {
+                    if matches!(self.log_level, LogLevel::Debug | LogLevel::Trace) {
+                        self.vm.messages.extend(std::iter::once(Message::warning(format!(
+                            "[debug] popping runnable (call_id {result_call_id}, result {result})"
+                        ))));
+                    }
                     self.top_runnable()
                         .external_question_result(result_call_id, result)?;
                     self.next_event()
@@ -112,6 +204,14 @@ impl Query {
         self.top_runnable().debug_command(command)
     }
 
+    // TODO: add persistent `break <rule_name>`/`break <file>:<line>` breakpoints, conditional
+    // breakpoints (`break <rule_name> if <polar-expr>`, evaluated as a sub-query over the current
+    // bindings), and `delete <n>` to the command set `debug_command` forwards into. That parser,
+    // the `maybe_break` hook it would plug into, and the bindings-per-step machinery it would
+    // need to consult all live in the VM's debugger implementation, which this tree doesn't
+    // include -- `Query` only forwards the raw command string to `top_runnable()`. `Query` itself
+    // has no breakpoint registry to add the new commands to without that VM-side support.
+
     pub fn next_message(&self) -> Option<Message> {
         self.vm.messages.next()
     }
@@ -141,10 +241,232 @@ impl Iterator for Query {
     }
 }
 
+/// Mirrors the VM's built-in `QUERY_TIMEOUT_S` default. Exposed so embedders can compute a
+/// multiple of it, or restore it explicitly after calling `Polar::set_query_timeout`.
+pub const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Single configuration entrypoint for `Polar` and `Query`, replacing the scattered env-var and
+/// hardcoded-default approach (`POLAR_IGNORE_NO_ALLOW_WARNING`, the VM's hardcoded query timeout
+/// and log level) with one struct embedders can set programmatically.
+#[derive(Debug, Clone)]
+pub struct PolarConfig {
+    /// Suppress the "no allow rule" warning `diagnostic_load` otherwise emits. Previously only
+    /// settable via the `POLAR_IGNORE_NO_ALLOW_WARNING` environment variable or
+    /// `set_ignore_no_allow_warning`.
+    pub ignore_no_allow_warning: bool,
+    /// Per-query wall-clock timeout. `None` keeps the VM's built-in default.
+    pub query_timeout: Option<Duration>,
+    /// Maximum VM log verbosity. `Info` (the default, or `POLAR_LOG` unset/unrecognized) stays at
+    /// today's high-level notices. `Debug` additionally has `Query::next_event` emit a message at
+    /// each runnable push/pop -- i.e. each rule-application/choice-point boundary between `Query`
+    /// and the `Runnable`s it drives. `Trace` additionally emits a message per `next_event` step,
+    /// the finest granularity reachable from this file (see `Query`'s `log_level` field doc for
+    /// why this is coarser than the VM's own internal goal/binding trace).
+    ///
+    /// Note: these messages are tagged `MessageKind::Warning` since `messages.rs` doesn't expose a
+    /// finer-grained kind in this snapshot -- embedders distinguish them from ordinary warnings by
+    /// their `"[debug]"`/`"[trace]"` prefix, not a structured field.
+    pub log_level: LogLevel,
+    /// Maximum number of goals the VM will execute before giving up on a query. `None` keeps the
+    /// VM's built-in `MAX_EXECUTED_GOALS` default. Guards against runaway recursive rules.
+    /// Enforced by `Query::next_event` (see its `max_goals` field), which returns
+    /// `RuntimeError::ResourceLimitExceeded` once the bound is hit instead of running away.
+    pub max_goals: Option<u64>,
+    /// Maximum number of variable bindings a query may push onto the binding stack. `None` keeps
+    /// the VM's built-in default. Guards against binding-explosion policies.
+    ///
+    /// Plumbed through to `PolarVirtualMachine::new` but, unlike `max_goals`, not yet enforced by
+    /// anything in this source snapshot: `Query` has no accessor onto the VM's binding stack to
+    /// check this against (that stack lives in `vm.rs`, outside this snapshot), so there's nowhere
+    /// here to add the bound check the way `Query::next_event` does for goals.
+    pub max_variable_bindings: Option<u64>,
+    /// Maximum depth of the binding stack's backtracking trail. `None` keeps the VM's built-in
+    /// default. Same caveat as `max_variable_bindings`: plumbed through, not yet enforced here.
+    pub max_binding_depth: Option<u64>,
+}
+
+impl Default for PolarConfig {
+    fn default() -> Self {
+        Self {
+            ignore_no_allow_warning: false,
+            query_timeout: None,
+            log_level: LogLevel::Info,
+            max_goals: None,
+            max_variable_bindings: None,
+            max_binding_depth: None,
+        }
+    }
+}
+
+impl PolarConfig {
+    pub fn builder() -> PolarConfigBuilder {
+        PolarConfigBuilder::default()
+    }
+}
+
+/// Builder for `PolarConfig`. Unset fields fall back to `PolarConfig::default()`'s values.
+#[derive(Debug, Default)]
+pub struct PolarConfigBuilder {
+    ignore_no_allow_warning: Option<bool>,
+    query_timeout: Option<Duration>,
+    log_level: Option<LogLevel>,
+    max_goals: Option<u64>,
+    max_variable_bindings: Option<u64>,
+    max_binding_depth: Option<u64>,
+}
+
+impl PolarConfigBuilder {
+    pub fn ignore_no_allow_warning(mut self, ignore_no_allow_warning: bool) -> Self {
+        self.ignore_no_allow_warning = Some(ignore_no_allow_warning);
+        self
+    }
+
+    pub fn query_timeout(mut self, query_timeout: Duration) -> Self {
+        self.query_timeout = Some(query_timeout);
+        self
+    }
+
+    pub fn log_level(mut self, log_level: LogLevel) -> Self {
+        self.log_level = Some(log_level);
+        self
+    }
+
+    pub fn max_goals(mut self, max_goals: u64) -> Self {
+        self.max_goals = Some(max_goals);
+        self
+    }
+
+    pub fn max_variable_bindings(mut self, max_variable_bindings: u64) -> Self {
+        self.max_variable_bindings = Some(max_variable_bindings);
+        self
+    }
+
+    pub fn max_binding_depth(mut self, max_binding_depth: u64) -> Self {
+        self.max_binding_depth = Some(max_binding_depth);
+        self
+    }
+
+    pub fn build(self) -> PolarConfig {
+        let defaults = PolarConfig::default();
+        PolarConfig {
+            ignore_no_allow_warning: self
+                .ignore_no_allow_warning
+                .unwrap_or(defaults.ignore_no_allow_warning),
+            query_timeout: self.query_timeout.or(defaults.query_timeout),
+            log_level: self.log_level.unwrap_or(defaults.log_level),
+            max_goals: self.max_goals.or(defaults.max_goals),
+            max_variable_bindings: self.max_variable_bindings.or(defaults.max_variable_bindings),
+            max_binding_depth: self.max_binding_depth.or(defaults.max_binding_depth),
+        }
+    }
+}
+
+/// The severity of a [`DiagnosticJson`], mirroring `Diagnostic`'s two variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+}
+
+/// A secondary ("related") location for a [`DiagnosticJson`], naming the file a shadowing
+/// rule/rule-type came from (see `KnowledgeBase::related_diagnostic_source_file`) but not a
+/// precise line/column -- this source snapshot has no `Term` byte-offset accessor to derive one
+/// from, so file-level is as fine-grained as this gets.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RelatedDiagnosticJson {
+    pub label: String,
+    pub file: Option<String>,
+}
+
+/// See `Polar::diagnostic_load_json` for what this deliberately does and doesn't carry.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DiagnosticJson {
+    pub severity: DiagnosticSeverity,
+    pub message: String,
+    /// A "did you mean `X`?" hint for a [`ValidationError::MissingRequiredRule`] whose name is a
+    /// near-miss for something actually declared in the KB (see
+    /// `KnowledgeBase::missing_rule_suggestion`). `None` for every other diagnostic kind, or when
+    /// nothing declared is close enough to guess.
+    pub suggestion: Option<String>,
+    /// Secondary spans for a [`ValidationError::RedundantRule`]/[`ValidationError::RedundantRuleType`]
+    /// pointing at the rule/template that shadows this one (see
+    /// `KnowledgeBase::related_diagnostic_source_file`). Empty for every other diagnostic kind.
+    pub related: Vec<RelatedDiagnosticJson>,
+}
+
+impl DiagnosticJson {
+    fn from_diagnostic(diagnostic: &Diagnostic, kb: &KnowledgeBase) -> Self {
+        let severity = if diagnostic.is_error() {
+            DiagnosticSeverity::Error
+        } else {
+            DiagnosticSeverity::Warning
+        };
+        let suggestion = match diagnostic {
+            Diagnostic::Error(error::PolarError {
+                kind: ErrorKind::Validation(ValidationError::MissingRequiredRule { rule }),
+                ..
+            }) => kb.missing_rule_suggestion(&rule.name),
+            _ => None,
+        };
+        let related = match diagnostic {
+            Diagnostic::Warning(error::PolarError {
+                kind: ErrorKind::Validation(ValidationError::RedundantRule { rule, shadowed_by }),
+                ..
+            }) => vec![RelatedDiagnosticJson {
+                label: format!("first matched by: {shadowed_by}"),
+                file: kb.related_diagnostic_source_file(rule),
+            }],
+            Diagnostic::Warning(error::PolarError {
+                kind:
+                    ErrorKind::Validation(ValidationError::RedundantRuleType {
+                        rule_type,
+                        shadowed_by,
+                    }),
+                ..
+            }) => vec![RelatedDiagnosticJson {
+                label: format!("already covered by: {shadowed_by}"),
+                file: kb.related_diagnostic_source_file(rule_type),
+            }],
+            _ => vec![],
+        };
+        DiagnosticJson {
+            severity,
+            message: diagnostic.to_string(),
+            suggestion,
+            related,
+        }
+    }
+}
+
+/// One parameter's specializer, as seen by [`PolicyMetadata`] -- the type tag if the parameter is
+/// constrained by a pattern (e.g. `user: User`), or `None` for an unconstrained parameter.
+pub type ParamSpecializer = Option<String>;
+
+/// A single loaded rule's shape, for the `rules` field of [`PolicyMetadata`]. A rule name may have
+/// more than one head with the same arity (overloads distinguished by specializers), so `heads`
+/// holds one entry per head rather than collapsing them.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RuleMetadata {
+    pub name: String,
+    pub arity: usize,
+    pub heads: Vec<Vec<ParamSpecializer>>,
+}
+
+/// Structured description of everything currently loaded into a `Polar` instance's knowledge
+/// base, returned by `Polar::inspect_policy`. See that method for what's included and what's
+/// deliberately left out.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PolicyMetadata {
+    pub rules: Vec<RuleMetadata>,
+    pub relation_graph: RelationGraph,
+    pub constants: Vec<String>,
+}
+
 pub struct Polar {
     pub kb: Arc<RwLock<KnowledgeBase>>,
     messages: MessageQueue,
-    ignore_no_allow_warning: bool,
+    config: PolarConfig,
 }
 
 impl Default for Polar {
@@ -156,90 +478,220 @@ impl Default for Polar {
 const MULTIPLE_LOAD_ERROR_MSG: &str =
     "Cannot load additional Polar code -- all Polar code must be loaded at the same time.";
 
+/// Parse `source` (already registered under `source_id` via `kb.add_source`) and install its
+/// rules, rule types, inline queries, and resource blocks into `kb`. Shared by `diagnostic_load`,
+/// which calls this once per freshly-added source, and `Polar::update_source`, which calls this
+/// for a single reloaded file after clearing out that file's previous contents.
+fn load_source_into_kb(
+    source_id: u64,
+    source: &Source,
+    kb: &mut KnowledgeBase,
+) -> PolarResult<Vec<Diagnostic>> {
+    let mut lines = parser::parse_lines(source_id, &source.src)
+        // TODO(gj): we still bomb out at the first ParseError.
+        .map_err(|e| e.set_context(Some(source), None))?;
+    lines.reverse();
+    let mut diagnostics = vec![];
+    while let Some(line) = lines.pop() {
+        match line {
+            parser::Line::Rule(rule) => {
+                diagnostics.append(&mut check_singletons(&rule, kb));
+                diagnostics.append(&mut check_ambiguous_precedence(&rule, kb));
+                let rule = rewrite_rule(rule, kb);
+                kb.add_rule(rule);
+            }
+            parser::Line::Query(term) => {
+                kb.inline_queries.push(term);
+            }
+            parser::Line::RuleType(rule_type) => {
+                // make sure rule_type doesn't have anything that needs to be rewritten in the head
+                let rule_type = rewrite_rule(rule_type, kb);
+                if !matches!(
+                    rule_type.body.value(),
+                    Value::Expression(
+                        Operation {
+                            operator: Operator::And,
+                            args
+                        }
+                    ) if args.is_empty()
+                ) {
+                    diagnostics.push(Diagnostic::Error(kb.set_error_context(
+                        &rule_type.body,
+                        error::ValidationError::InvalidRuleType {
+                            rule_type: rule_type.to_polar(),
+                            msg: "\nRule types cannot contain dot lookups.".to_owned(),
+                        },
+                    )));
+                } else {
+                    kb.add_rule_type(rule_type);
+                }
+            }
+            parser::Line::ResourceBlock {
+                keyword,
+                resource,
+                productions,
+            } => match resource_block_from_productions(keyword, resource, productions)
+                .map(|block| block.add_to_kb(kb))
+            {
+                Ok(errors) | Err(errors) => {
+                    diagnostics.append(&mut errors.into_iter().map(Diagnostic::Error).collect())
+                }
+            },
+        }
+    }
+    Ok(diagnostics)
+}
+
+/// Parse `POLAR_LOG` (`"trace"`, `"debug"`, or `"info"`, case-insensitive) into a `LogLevel`.
+/// Mirrors `POLAR_IGNORE_NO_ALLOW_WARNING`'s env-var-based configuration for embedders that
+/// haven't moved to `PolarConfig` yet. Returns `None` (falling back to `PolarConfig::default()`'s
+/// level) if the variable is unset or unrecognized.
+fn log_level_from_env() -> Option<LogLevel> {
+    match std::env::var("POLAR_LOG").ok()?.to_lowercase().as_str() {
+        "trace" => Some(LogLevel::Trace),
+        "debug" => Some(LogLevel::Debug),
+        "info" => Some(LogLevel::Info),
+        _ => None,
+    }
+}
+
 impl Polar {
     pub fn new() -> Self {
-        // TODO(@gkaemmer): pulling this from an environment variable is a hack
-        // and should not be used for similar cases. See set_ignore_no_allow_warning.
-        // Ideally, we'd have a single "configuration" entrypoint for both the Polar
-        // and Query types, so that we don't have to keep adding environment
-        // variables for new configuration use-cases.
+        // Preserved for backwards compatibility with embedders relying on the env vars; prefer
+        // `Polar::with_config` going forward, which doesn't require touching the environment.
         let ignore_no_allow_warning = std::env::var("POLAR_IGNORE_NO_ALLOW_WARNING").is_ok();
+        let mut builder =
+            PolarConfig::builder().ignore_no_allow_warning(ignore_no_allow_warning);
+        if let Some(log_level) = log_level_from_env() {
+            builder = builder.log_level(log_level);
+        }
+        Self::with_config(builder.build())
+    }
+
+    /// Construct a `Polar` with an explicit `PolarConfig`, e.g. to set a query timeout or log
+    /// level without recompiling or reaching for environment variables.
+    pub fn with_config(config: PolarConfig) -> Self {
         Self {
             kb: Arc::new(RwLock::new(KnowledgeBase::new())),
             messages: MessageQueue::new(),
-            ignore_no_allow_warning,
+            config,
         }
     }
 
+    /// Like `diagnostic_load`, but for a single inline source string instead of a `Vec<Source>`,
+    /// mirroring how `load_str` relates to `load`. Runs every validation pass -- rule-type
+    /// matching (including every missing required rule, not just the first), singleton
+    /// variables, unknown specializers, and the rest of `KnowledgeBase::validate_rules` -- to
+    /// completion and returns the full set of diagnostics with source positions, so a policy
+    /// author or editor integration sees every problem at once instead of a fix-one-rerun loop.
+    pub fn validate_str(&self, src: &str) -> Vec<Diagnostic> {
+        self.diagnostic_load(vec![Source {
+            src: src.to_owned(),
+            filename: None,
+        }])
+    }
+
     /// Load `sources` into the KB, returning compile-time diagnostics accumulated during the load.
     pub fn diagnostic_load(&self, sources: Vec<Source>) -> Vec<Diagnostic> {
-        // we extract this into a separate function
-        // so that any errors returned with `?` are captured
-        fn load_source(
-            source_id: u64,
-            source: &Source,
-            kb: &mut KnowledgeBase,
-        ) -> PolarResult<Vec<Diagnostic>> {
-            let mut lines = parser::parse_lines(source_id, &source.src)
-                // TODO(gj): we still bomb out at the first ParseError.
-                .map_err(|e| e.set_context(Some(source), None))?;
-            lines.reverse();
-            let mut diagnostics = vec![];
-            while let Some(line) = lines.pop() {
-                match line {
-                    parser::Line::Rule(rule) => {
-                        diagnostics.append(&mut check_singletons(&rule, kb));
-                        diagnostics.append(&mut check_ambiguous_precedence(&rule, kb));
-                        let rule = rewrite_rule(rule, kb);
-                        kb.add_rule(rule);
-                    }
-                    parser::Line::Query(term) => {
-                        kb.inline_queries.push(term);
-                    }
-                    parser::Line::RuleType(rule_type) => {
-                        // make sure rule_type doesn't have anything that needs to be rewritten in the head
-                        let rule_type = rewrite_rule(rule_type, kb);
-                        if !matches!(
-                            rule_type.body.value(),
-                            Value::Expression(
-                                Operation {
-                                    operator: Operator::And,
-                                    args
-                                }
-                            ) if args.is_empty()
-                        ) {
-                            diagnostics.push(Diagnostic::Error(kb.set_error_context(
-                                &rule_type.body,
-                                error::ValidationError::InvalidRuleType {
-                                    rule_type: rule_type.to_polar(),
-                                    msg: "\nRule types cannot contain dot lookups.".to_owned(),
-                                },
-                            )));
-                        } else {
-                            kb.add_rule_type(rule_type);
-                        }
-                    }
-                    parser::Line::ResourceBlock {
-                        keyword,
-                        resource,
-                        productions,
-                    } => match resource_block_from_productions(keyword, resource, productions)
-                        .map(|block| block.add_to_kb(kb))
-                    {
-                        Ok(errors) | Err(errors) => diagnostics
-                            .append(&mut errors.into_iter().map(Diagnostic::Error).collect()),
-                    },
+        let mut kb = self.kb.write().unwrap();
+        Self::diagnostic_load_into(&mut kb, sources, self.config.ignore_no_allow_warning)
+    }
+
+    /// Like `diagnostic_load`, but renders the diagnostics as a JSON array instead of `Diagnostic`
+    /// values, for embedders (editor/LSP integrations, log pipelines) that want to consume load
+    /// results without linking against `polar-core`'s Rust types.
+    ///
+    /// This is a partial representation: each entry carries `severity`, the diagnostic's
+    /// rendered `message`, a best-effort "did you mean" `suggestion`, and, for redundant-rule and
+    /// redundant-rule-type warnings, a `related` list of labels with the file of the shadowing
+    /// rule or template. No entry carries an error code, a source filename of its own, or a
+    /// byte-offset/line-column range.
+    pub fn diagnostic_load_json(&self, sources: Vec<Source>) -> String {
+        let raw_diagnostics = self.diagnostic_load(sources);
+        let kb = self.kb.read().unwrap();
+        let diagnostics: Vec<DiagnosticJson> = raw_diagnostics
+            .iter()
+            .map(|d| DiagnosticJson::from_diagnostic(d, &kb))
+            .collect();
+        serde_json::to_string(&diagnostics).unwrap()
+    }
+
+    /// Walk the currently-loaded knowledge base and return a structured description of the
+    /// policy: every rule name with its arity and per-head parameter specializers, the resource
+    /// block/actor block relation graph (see `KnowledgeBase::export_relation_graph`), and the
+    /// names of registered constants and classes. Lets tooling (editor autocomplete, policy
+    /// linters, docs generation, authorization-coverage dashboards) introspect a loaded `Polar`
+    /// instance without re-parsing its source.
+    ///
+    /// This doesn't include MROs -- `KnowledgeBase` only exposes them keyed by already-registered
+    /// class name (via `add_mro`), with no getter to read them back, so there's nothing to walk
+    /// here beyond what `constants` already lists. It also doesn't break resource blocks out by
+    /// declared roles/permissions individually, since `ResourceBlocks`' internal role/permission
+    /// bookkeeping isn't exposed past the relation graph `export_relation_graph` already produces.
+    pub fn inspect_policy(&self) -> PolicyMetadata {
+        let kb = self.kb.read().unwrap();
+
+        let rules = kb
+            .get_rules()
+            .values()
+            .map(|generic_rule| {
+                let heads: Vec<Vec<ParamSpecializer>> = generic_rule
+                    .rules
+                    .values()
+                    .map(|rule| {
+                        rule.params
+                            .iter()
+                            .map(|param| match param.specializer.as_ref().map(Term::value) {
+                                Some(Value::Pattern(Pattern::Instance(InstanceLiteral {
+                                    tag,
+                                    ..
+                                }))) => Some(tag.0.clone()),
+                                _ => None,
+                            })
+                            .collect()
+                    })
+                    .collect();
+                let arity = heads.first().map(Vec::len).unwrap_or(0);
+                RuleMetadata {
+                    name: generic_rule.name.0.clone(),
+                    arity,
+                    heads,
                 }
-            }
-            Ok(diagnostics)
+            })
+            .collect();
+
+        let constants = kb
+            .get_registered_constants()
+            .keys()
+            .map(|name| name.0.clone())
+            .collect();
+
+        PolicyMetadata {
+            rules,
+            relation_graph: kb.export_relation_graph(),
+            constants,
         }
+    }
 
-        let mut kb = self.kb.write().unwrap();
+    /// Like `inspect_policy`, but renders the result as a JSON string, for embedders that want to
+    /// consume it without linking against `polar-core`'s Rust types.
+    pub fn inspect_policy_json(&self) -> String {
+        serde_json::to_string(&self.inspect_policy()).unwrap()
+    }
+
+    /// Core of `diagnostic_load`, extracted so `reload` can run it against a fresh, not-yet-live
+    /// `KnowledgeBase` before deciding whether to swap it into `self.kb`.
+    fn diagnostic_load_into(
+        kb: &mut KnowledgeBase,
+        sources: Vec<Source>,
+        ignore_no_allow_warning: bool,
+    ) -> Vec<Diagnostic> {
         let mut diagnostics = vec![];
 
         for source in &sources {
             let result = kb.add_source(source.clone());
-            let result = result.and_then(|source_id| load_source(source_id, source, &mut kb));
+            let result =
+                result.and_then(|source_id| load_source_into_kb(source_id, source, &mut *kb));
             match result {
                 Ok(mut ds) => diagnostics.append(&mut ds),
                 Err(e) => diagnostics.push(Diagnostic::Error(e)),
@@ -295,14 +747,14 @@ impl Polar {
         diagnostics.append(&mut kb.validate_rules());
 
         // Perform validation checks against the whole policy
-        if !self.ignore_no_allow_warning {
-            if let Some(w) = check_no_allow_rule(&kb) {
+        if !ignore_no_allow_warning {
+            if let Some(w) = check_no_allow_rule(kb) {
                 diagnostics.push(w)
             }
         }
 
         // Check for has_permission calls alongside resource block definitions
-        if let Some(w) = check_resource_blocks_missing_has_permission(&kb) {
+        if let Some(w) = check_resource_blocks_missing_has_permission(kb) {
             diagnostics.push(w)
         };
 
@@ -314,6 +766,39 @@ impl Polar {
         diagnostics
     }
 
+    /// Atomically reload `sources` as the entire policy: runs the full `diagnostic_load_into`
+    /// pipeline against a fresh `KnowledgeBase` (carrying forward this `Polar`'s already-registered
+    /// constants, class aliases, and MROs) and only swaps it in for `self.kb` if doing so produced
+    /// zero error-level diagnostics.
+    ///
+    /// Unlike `load`, which refuses any second load, this may be called repeatedly with a
+    /// completely new set of `sources` -- e.g. when a long-running embedder watches policy files
+    /// on disk and wants to apply edits without throwing away the `Polar` instance. On error, the
+    /// current KB (and any `Query` already in flight against it) is left untouched.
+    pub fn reload(&self, sources: Vec<Source>) -> PolarResult<()> {
+        let mut fresh_kb = KnowledgeBase::new();
+        fresh_kb.copy_registrations_from(&self.kb.read().unwrap());
+
+        let diagnostics =
+            Self::diagnostic_load_into(&mut fresh_kb, sources, self.config.ignore_no_allow_warning);
+
+        let (mut errors, mut warnings) = (vec![], vec![]);
+        for diagnostic in diagnostics {
+            match diagnostic {
+                Diagnostic::Error(e) => errors.push(e),
+                Diagnostic::Warning(w) => warnings.push(w),
+            }
+        }
+        if let Some(e) = errors.into_iter().next() {
+            return Err(e);
+        }
+
+        *self.kb.write().unwrap() = fresh_kb;
+        self.messages
+            .extend(warnings.into_iter().map(Message::warning));
+        Ok(())
+    }
+
     /// Load `Source`s into the KB.
     pub fn load(&self, sources: Vec<Source>) -> PolarResult<()> {
         if self.kb.read().unwrap().has_rules() {
@@ -338,6 +823,28 @@ impl Polar {
         Ok(())
     }
 
+    /// Reload a single named file that was previously loaded, without clearing the rest of the
+    /// knowledge base: rules and inline queries derived from the file's previous contents are
+    /// removed and replaced by parsing `new_src`, while rules from other files, registered
+    /// constants, and MROs are left untouched. The previous contents' entry in `Sources` is not
+    /// removed; `new_src` is registered under a new source id alongside it.
+    ///
+    /// Unlike `load`, this does not require that the KB be empty, and may be called repeatedly as
+    /// `filename` changes, supporting editor/IDE-style live policy editing.
+    pub fn update_source(&self, filename: &str, new_src: String) -> PolarResult<Vec<Diagnostic>> {
+        let mut kb = self.kb.write().unwrap();
+        kb.remove_source(filename)?;
+
+        let source = Source {
+            src: new_src,
+            filename: Some(filename.to_owned()),
+        };
+        let diagnostics = kb
+            .add_source(source.clone())
+            .and_then(|source_id| load_source_into_kb(source_id, &source, &mut kb))?;
+        Ok(diagnostics)
+    }
+
     // Used in integration tests
     pub fn load_str(&self, src: &str) -> PolarResult<()> {
         self.load(vec![Source {
@@ -373,15 +880,43 @@ impl Polar {
         Ok(self.new_query_from_term(term, trace))
     }
 
-    pub fn new_query_from_term(&self, mut term: Term, trace: bool) -> Query {
+    pub fn new_query_from_term(&self, term: Term, trace: bool) -> Query {
+        self.new_query_from_term_with_config(term, trace, None)
+    }
+
+    /// Like `new_query_from_term`, but `config_override`, when given, is used in place of this
+    /// `Polar`'s own `PolarConfig` for just this query -- e.g. to give one long-running query a
+    /// longer timeout without changing the default for every other query.
+    pub fn new_query_from_term_with_config(
+        &self,
+        mut term: Term,
+        trace: bool,
+        config_override: Option<PolarConfig>,
+    ) -> Query {
         {
             let mut kb = self.kb.write().unwrap();
             term = rewrite_term(term, &mut kb);
         }
+        let config = config_override.unwrap_or_else(|| self.config.clone());
         let query = Goal::Query { term: term.clone() };
-        let vm =
-            PolarVirtualMachine::new(self.kb.clone(), trace, vec![query], self.messages.clone());
-        Query::new(vm, term)
+        let vm = PolarVirtualMachine::new(
+            self.kb.clone(),
+            trace,
+            vec![query],
+            self.messages.clone(),
+            config.query_timeout,
+            config.log_level,
+            config.max_goals,
+            config.max_variable_bindings,
+            config.max_binding_depth,
+        );
+        Query::new(
+            vm,
+            term,
+            config.query_timeout,
+            config.max_goals,
+            config.log_level,
+        )
     }
 
     // @TODO: Direct load_rules endpoint.
@@ -418,11 +953,55 @@ impl Polar {
         build_filter_plan(types, partial_results, variable, class_tag)
     }
 
-    // TODO(@gkaemmer): this is a hack and should not be used for similar cases.
-    // Ideally, we'd have a single "configuration" entrypoint for both the Polar
-    // and Query types.
+    // TODO: `build_filter_plan` only covers the *consumer* side of data filtering -- turning
+    // `PartialResults` the VM already collected into a `FilterPlan`. Producing those
+    // `PartialResults` in the first place (accumulating constraints on an unbound query variable
+    // as goals run, then normalizing with `simplify_partial` so trivially-true conjuncts and
+    // commutative duplicates drop out) still has to happen inside the VM's goal evaluation loop,
+    // which lives in `vm.rs` -- outside this source snapshot. There's no `Query`-level entry
+    // point here for running a query with a variable deliberately left unbound and getting a
+    // constraint expression back instead of enumerated results; adding one means extending
+    // `vm.rs` itself. The union-find pass over collected `=`/`Unify` conjuncts that collapses
+    // aliased variables to one representative *is* pure syntactic term manipulation with no VM
+    // dependency, though, so that sub-piece is pulled out as `kb::partition_equivs` and usable
+    // once a caller has a flat conjunct list to feed it -- this just isn't wired up to anything
+    // that can produce `PartialResults` yet.
+
+    /// Prefer `PolarConfig`/`Polar::with_config` for new code; kept for callers that already hold
+    /// a `&mut Polar` and just need to flip this one flag.
     pub fn set_ignore_no_allow_warning(&mut self, ignore: bool) {
-        self.ignore_no_allow_warning = ignore;
+        self.config.ignore_no_allow_warning = ignore;
+    }
+
+    /// Prefer `PolarConfig`/`Polar::with_config` for new code; kept for callers that already hold
+    /// a `&mut Polar` and just need to change the default query timeout, e.g. to
+    /// `DEFAULT_QUERY_TIMEOUT` to restore the VM's built-in default.
+    pub fn set_query_timeout(&mut self, query_timeout: Duration) {
+        self.config.query_timeout = Some(query_timeout);
+    }
+
+    /// Prefer `PolarConfig`/`Polar::with_config` for new code; kept for callers that already hold
+    /// a `&mut Polar` and just need to change the maximum log verbosity, e.g. to `LogLevel::Trace`
+    /// while debugging a specific policy without restarting with `POLAR_LOG` set.
+    pub fn set_log_level(&mut self, log_level: LogLevel) {
+        self.config.log_level = log_level;
+    }
+
+    /// Prefer `PolarConfig`/`Polar::with_config` for new code; kept for callers that already hold
+    /// a `&mut Polar` and just need to cap the number of goals a query may execute before it
+    /// fails with `RuntimeError::ResourceLimitExceeded` instead of running away.
+    pub fn set_max_goals(&mut self, max_goals: u64) {
+        self.config.max_goals = Some(max_goals);
+    }
+
+    /// See `set_max_goals`; caps the number of variable bindings a query may push instead.
+    pub fn set_max_variable_bindings(&mut self, max_variable_bindings: u64) {
+        self.config.max_variable_bindings = Some(max_variable_bindings);
+    }
+
+    /// See `set_max_goals`; caps the depth of the binding stack's backtracking trail instead.
+    pub fn set_max_binding_depth(&mut self, max_binding_depth: u64) {
+        self.config.max_binding_depth = Some(max_binding_depth);
     }
 }
 
@@ -437,6 +1016,92 @@ mod tests {
         let _ = polar.load_str("f(_);");
     }
 
+    #[test]
+    fn with_config_sets_ignore_no_allow_warning_without_the_env_var() {
+        let polar = Polar::with_config(
+            PolarConfig::builder()
+                .ignore_no_allow_warning(true)
+                .query_timeout(Duration::from_secs(5))
+                .log_level(LogLevel::Debug)
+                .build(),
+        );
+
+        // No "missing allow rule" warning, even though the policy has none, because the config
+        // turned that warning off -- without touching POLAR_IGNORE_NO_ALLOW_WARNING.
+        let diagnostics = polar.diagnostic_load(vec![Source {
+            src: "f(_);".to_owned(),
+            filename: None,
+        }]);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn set_log_level_updates_the_active_config() {
+        let mut polar = Polar::new();
+        assert!(matches!(polar.config.log_level, LogLevel::Info));
+
+        polar.set_log_level(LogLevel::Trace);
+        assert!(matches!(polar.config.log_level, LogLevel::Trace));
+    }
+
+    #[test]
+    fn trace_log_level_emits_a_message_per_query_step() {
+        let polar = Polar::with_config(PolarConfig::builder().log_level(LogLevel::Trace).build());
+        polar.load_str("f(_);").unwrap();
+        // Drain the load-time "no allow rule" warning, which shares the query's message queue.
+        while polar.next_message().is_some() {}
+        let mut query = polar.new_query("f(1)", false).unwrap();
+        query.next_event().unwrap();
+
+        let msg = query.next_message().expect("trace message");
+        assert!(matches!(msg.kind, MessageKind::Warning));
+        assert!(msg.msg.starts_with("[trace] step"));
+    }
+
+    #[test]
+    fn info_log_level_emits_no_step_messages() {
+        let polar = Polar::new();
+        polar.load_str("f(_);").unwrap();
+        // Drain the load-time "no allow rule" warning, which shares the query's message queue.
+        while polar.next_message().is_some() {}
+        let mut query = polar.new_query("f(1)", false).unwrap();
+        query.next_event().unwrap();
+
+        assert!(query.next_message().is_none());
+    }
+
+    #[test]
+    fn unset_builder_fields_fall_back_to_defaults() {
+        let config = PolarConfig::builder().ignore_no_allow_warning(true).build();
+        assert!(config.ignore_no_allow_warning);
+        assert_eq!(config.query_timeout, PolarConfig::default().query_timeout);
+        assert_eq!(config.log_level, PolarConfig::default().log_level);
+        assert_eq!(config.max_goals, PolarConfig::default().max_goals);
+        assert_eq!(
+            config.max_variable_bindings,
+            PolarConfig::default().max_variable_bindings
+        );
+        assert_eq!(
+            config.max_binding_depth,
+            PolarConfig::default().max_binding_depth
+        );
+    }
+
+    #[test]
+    fn set_max_goals_updates_the_active_config() {
+        let mut polar = Polar::new();
+        assert_eq!(polar.config.max_goals, None);
+
+        polar.set_max_goals(1000);
+        assert_eq!(polar.config.max_goals, Some(1000));
+
+        polar.set_max_variable_bindings(500);
+        assert_eq!(polar.config.max_variable_bindings, Some(500));
+
+        polar.set_max_binding_depth(100);
+        assert_eq!(polar.config.max_binding_depth, Some(100));
+    }
+
     #[test]
     fn loading_a_second_time_fails() {
         let polar = Polar::new();
@@ -490,6 +1155,73 @@ mod tests {
         assert!(!polar.kb.read().unwrap().has_rules());
     }
 
+    #[test]
+    fn update_source_reloads_one_file_without_disturbing_others() {
+        let polar = Polar::new();
+        polar
+            .load(vec![Source {
+                src: "f(1);".to_owned(),
+                filename: Some("a".to_owned()),
+            }])
+            .unwrap();
+
+        // Reloading "a" with different rules should not be rejected the way a second `load` of
+        // the same filename would be, and should leave unrelated knowledge intact.
+        polar
+            .update_source("a", "f(2);".to_owned())
+            .unwrap()
+            .into_iter()
+            .for_each(|d| assert!(!matches!(d, Diagnostic::Error(_))));
+
+        let kb = polar.kb.read().unwrap();
+        let rule = kb.get_generic_rule(&sym!("f")).unwrap();
+        assert_eq!(rule.rules.len(), 1);
+    }
+
+    #[test]
+    fn reload_swaps_in_a_fresh_kb_only_on_success() {
+        let polar = Polar::new();
+        polar
+            .register_constant(
+                sym!("Fruit"),
+                term!(Value::ExternalInstance(ExternalInstance {
+                    instance_id: 1,
+                    constructor: None,
+                    repr: None
+                })),
+            )
+            .unwrap();
+        polar.register_mro(sym!("Fruit"), vec![1]).unwrap();
+        polar.load_str("f(1);").unwrap();
+
+        // A successful reload entirely replaces the rules, but the registered class and its MRO
+        // (which come from the host, not the policy text) carry over.
+        polar
+            .reload(vec![Source {
+                src: "f(2);".to_owned(),
+                filename: None,
+            }])
+            .unwrap();
+        {
+            let kb = polar.kb.read().unwrap();
+            let rule = kb.get_generic_rule(&sym!("f")).unwrap();
+            assert_eq!(rule.rules.len(), 1);
+            assert!(kb.is_constant(&sym!("Fruit")));
+        }
+
+        // A reload that fails to parse leaves the current KB -- and its rules -- untouched.
+        assert!(polar
+            .reload(vec![Source {
+                src: "f(;".to_owned(),
+                filename: None,
+            }])
+            .is_err());
+        let kb = polar.kb.read().unwrap();
+        let rule = kb.get_generic_rule(&sym!("f")).unwrap();
+        assert_eq!(rule.rules.len(), 1);
+        assert!(kb.is_constant(&sym!("Fruit")));
+    }
+
     #[test]
     fn diagnostic_load_returns_multiple_diagnostics() {
         let polar = Polar::new();
@@ -518,4 +1250,164 @@ mod tests {
         );
         assert!(!polar.kb.read().unwrap().has_rules());
     }
+
+    #[test]
+    fn cancel_token_aborts_query_with_a_done_event() {
+        let polar = Polar::new();
+        polar.load_str("f(_);").unwrap();
+        let mut query = polar.new_query("f(1)", false).unwrap();
+
+        let token = query.cancel_token();
+        token.cancel();
+
+        assert!(matches!(
+            query.next_event().unwrap(),
+            QueryEvent::Done { .. }
+        ));
+    }
+
+    #[test]
+    fn query_timeout_surfaces_a_distinct_runtime_error() {
+        let polar = Polar::with_config(
+            PolarConfig::builder()
+                .query_timeout(Duration::from_secs(0))
+                .build(),
+        );
+        polar.load_str("f(_);").unwrap();
+        let mut query = polar.new_query("f(1)", false).unwrap();
+
+        // A zero-duration timeout has already elapsed by the time we ask for the first event.
+        let err = query.next_event().unwrap_err();
+        assert!(matches!(
+            err.kind,
+            ErrorKind::Runtime(error::RuntimeError::QueryTimeout { .. })
+        ));
+    }
+
+    #[test]
+    fn diagnostic_load_json_reports_severity_and_message() {
+        let polar = Polar::new();
+        let source = Source {
+            src: "f() if g();".to_owned(),
+            filename: Some("file".to_owned()),
+        };
+
+        let json = polar.diagnostic_load_json(vec![source]);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let diagnostics = parsed.as_array().unwrap();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0]["severity"], "error");
+        assert!(diagnostics[0]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Call to undefined rule \"g\""));
+        assert_eq!(diagnostics[1]["severity"], "warning");
+    }
+
+    #[test]
+    fn diagnostic_load_json_suggests_a_near_miss_for_a_missing_required_rule() {
+        let polar = Polar::new();
+        polar
+            .kb
+            .write()
+            .unwrap()
+            .add_rule_type(rule!("has_relation", ["x"; value!(1)], true));
+
+        let source = Source {
+            src: "has_relaton(x) if x = 1;".to_owned(),
+            filename: None,
+        };
+
+        let json = polar.diagnostic_load_json(vec![source]);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let diagnostics = parsed.as_array().unwrap();
+        let missing_required_rule = diagnostics
+            .iter()
+            .find(|d| d["message"].as_str().unwrap().contains("has_relation"))
+            .unwrap();
+        assert_eq!(missing_required_rule["suggestion"], "has_relaton");
+    }
+
+    #[test]
+    fn diagnostic_load_json_includes_related_span_for_a_redundant_rule() {
+        let polar = Polar::new();
+        let source = Source {
+            src: "f(1); f(1);".to_owned(),
+            filename: Some("policy.polar".to_owned()),
+        };
+
+        let json = polar.diagnostic_load_json(vec![source]);
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let diagnostics = parsed.as_array().unwrap();
+        let redundant_rule = diagnostics
+            .iter()
+            .find(|d| d["severity"] == "warning")
+            .unwrap();
+        let related = redundant_rule["related"].as_array().unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0]["file"], "policy.polar");
+    }
+
+    #[test]
+    fn inspect_policy_reports_rules_and_constants() {
+        let polar = Polar::new();
+        polar.register_constant(sym!("User"), term!(1)).unwrap();
+        polar
+            .load_str("allow(user: User, \"read\", resource) if user = resource; allow(_, _, _) if false;")
+            .unwrap();
+
+        let metadata = polar.inspect_policy();
+
+        let allow = metadata
+            .rules
+            .iter()
+            .find(|rule| rule.name == "allow")
+            .unwrap();
+        assert_eq!(allow.arity, 3);
+        assert_eq!(allow.heads.len(), 2);
+        assert_eq!(allow.heads[0][0], Some("User".to_owned()));
+        assert_eq!(allow.heads[1][0], None);
+
+        assert!(metadata.constants.contains(&"User".to_owned()));
+    }
+
+    #[test]
+    fn inspect_policy_json_round_trips_through_serde() {
+        let polar = Polar::new();
+        polar.load_str("f(_);").unwrap();
+
+        let json = polar.inspect_policy_json();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["rules"][0]["name"], "f");
+    }
+
+    #[test]
+    fn validate_str_reports_every_missing_required_rule_together() {
+        let polar = Polar::new();
+        polar
+            .kb
+            .write()
+            .unwrap()
+            .add_rule_type(rule!("has_relation", ["x"; value!(1)], true));
+        polar
+            .kb
+            .write()
+            .unwrap()
+            .add_rule_type(rule!("has_permission", ["x"; value!(1)], true));
+
+        let diagnostics = polar.validate_str("f(_);");
+        let missing = diagnostics
+            .iter()
+            .filter(|d| {
+                matches!(
+                    d,
+                    Diagnostic::Error(error::PolarError {
+                        kind: ErrorKind::Validation(ValidationError::MissingRequiredRule { .. }),
+                        ..
+                    })
+                )
+            })
+            .count();
+        assert_eq!(missing, 2);
+    }
 }
@@ -12,7 +12,7 @@ use polar_core::{
     error::*,
     events::*,
     messages::*,
-    polar::{Polar, Query},
+    polar::{Polar, PolarConfig, Query},
     sym, term,
     terms::*,
     traces::*,
@@ -45,6 +45,22 @@ fn no_debug(_: &str) -> String {
     "".to_string()
 }
 
+/// Default `ExternalOp` handler: resolves `==`/`!=`/unification structurally, since those don't
+/// require host cooperation to decide. Ordering operators (`<`, `<=`, `>`, `>=`) have no sensible
+/// default for opaque externals -- tests that compare external instances by order need to pass
+/// their own handler, e.g. one backed by `MockExternal`.
+fn default_external_op(operator: Operator, args: Vec<Term>) -> bool {
+    match operator {
+        Operator::Eq | Operator::Unify => args[0] == args[1],
+        Operator::Neq => args[0] != args[1],
+        _ => panic!(
+            "no default handler for external comparison operator {:?}; pass an \
+             external_op_handler to query_results!",
+            operator
+        ),
+    }
+}
+
 type QueryResults = Vec<(HashMap<Symbol, Value>, Option<TraceResult>)>;
 
 fn no_error_handler(e: PolarError) -> QueryResults {
@@ -60,7 +76,7 @@ fn no_is_subspecializer(_: u64, _: Symbol, _: Symbol) -> bool {
 }
 
 #[allow(clippy::too_many_arguments)]
-fn query_results<F, G, H, I, J, K, L>(
+fn query_results<F, G, H, I, J, K, L, M>(
     mut query: Query,
     mut external_call_handler: F,
     mut make_external_handler: H,
@@ -69,6 +85,7 @@ fn query_results<F, G, H, I, J, K, L>(
     mut debug_handler: G,
     mut message_handler: K,
     mut error_handler: L,
+    mut external_op_handler: M,
 ) -> QueryResults
 where
     F: FnMut(u64, Term, Symbol, Option<Vec<Term>>, Option<BTreeMap<Symbol, Term>>) -> Option<Term>,
@@ -78,6 +95,7 @@ where
     J: FnMut(u64, Symbol, Symbol) -> bool,
     K: FnMut(&Message),
     L: FnMut(PolarError) -> QueryResults,
+    M: FnMut(Operator, Vec<Term>) -> bool,
 {
     let mut results = vec![];
     loop {
@@ -144,11 +162,13 @@ where
                 query.debug_command(&debug_handler(message)).unwrap();
             }
             QueryEvent::ExternalOp {
-                operator: Operator::Eq,
+                operator,
                 call_id,
                 args,
                 ..
-            } => query.question_result(call_id, args[0] == args[1]).unwrap(),
+            } => query
+                .question_result(call_id, external_op_handler(operator, args))
+                .unwrap(),
             _ => {}
         }
     }
@@ -166,6 +186,7 @@ macro_rules! query_results {
             no_debug,
             print_messages,
             no_error_handler,
+            default_external_op,
         )
     };
     ($query:expr, $external_call_handler:expr, $make_external_handler:expr, $debug_handler:expr) => {
@@ -178,6 +199,7 @@ macro_rules! query_results {
             $debug_handler,
             print_messages,
             no_error_handler,
+            default_external_op,
         )
     };
     ($query:expr, $external_call_handler:expr) => {
@@ -190,6 +212,7 @@ macro_rules! query_results {
             no_debug,
             print_messages,
             no_error_handler,
+            default_external_op,
         )
     };
     ($query:expr, @msgs $message_handler:expr) => {
@@ -202,6 +225,7 @@ macro_rules! query_results {
             no_debug,
             $message_handler,
             no_error_handler,
+            default_external_op,
         )
     };
     ($query:expr, @errs $error_handler:expr) => {
@@ -214,6 +238,20 @@ macro_rules! query_results {
             no_debug,
             print_messages,
             $error_handler,
+            default_external_op,
+        )
+    };
+    ($query:expr, @ops $external_op_handler:expr) => {
+        query_results(
+            $query,
+            no_results,
+            no_externals,
+            no_isa,
+            no_is_subspecializer,
+            no_debug,
+            print_messages,
+            no_error_handler,
+            $external_op_handler,
         )
     };
 }
@@ -230,6 +268,7 @@ fn query_results_with_externals(query: Query) -> (QueryResults, MockExternal) {
             no_debug,
             print_messages,
             no_error_handler,
+            default_external_op,
         ),
         mock.into_inner(),
     )
@@ -1069,12 +1108,25 @@ fn test_external_call() -> TestResult {
     Ok(())
 }
 #[test]
-#[ignore] // ignore because this take a LONG time (could consider lowering the goal limit)
-#[should_panic(expected = "Goal count exceeded! MAX_EXECUTED_GOALS = 10000")]
 fn test_infinite_loop() {
-    let mut p = polar();
+    // `max_goals` bounds the number of `Query::next_event` steps rather than panicking partway
+    // through a runaway recursive rule -- see `PolarConfig::max_goals`'s doc comment for exactly
+    // what this counts and why it's a `Query`-level approximation rather than the VM's own
+    // internal goal counter.
+    let mut p = Polar::with_config(PolarConfig::builder().max_goals(1000).build());
+    p.set_ignore_no_allow_warning(true);
     p.load_str("f(x) if f(x);").unwrap();
-    qeval(&mut p, "f(1)");
+    let mut query = p.new_query("f(1)", false).unwrap();
+    let err = loop {
+        match query.next_event() {
+            Ok(_) => continue,
+            Err(e) => break e,
+        }
+    };
+    assert!(matches!(
+        err.kind,
+        ErrorKind::Runtime(RuntimeError::ResourceLimitExceeded { .. })
+    ));
 }
 
 #[test]
@@ -1184,6 +1236,14 @@ fn test_comparisons() -> TestResult {
 
     qeval(&mut p, "x == y and x = 1 and y = 1");
     qnull(&mut p, "x == y and x = 1 and y = 2");
+
+    // Integer/float coercion is numeric-only -- it never reaches across to strings.
+    qnull(&mut p, "1 == \"1\"");
+    qnull(&mut p, "\"1\" == 1");
+
+    // The coercion applies however `x` got its integer value, not just to literals.
+    qeval(&mut p, "x = 1 and x == 1.0");
+    qeval(&mut p, "x = 1.0 and x == 1");
     Ok(())
 }
 
@@ -1259,6 +1319,11 @@ fn test_arithmetic() -> TestResult {
     qeval(&mut p, "odd(3)");
     qnull(&mut p, "odd(4)");
 
+    // TODO: these two should transparently promote to an arbitrary-precision representation
+    // instead of raising ArithmeticError, per the bigint proposal for `Numeric`. That needs a
+    // new `Numeric` variant and checked-overflow-then-promote logic in `+`/`-`/`*`/`mod`/`rem`,
+    // plus a host-FFI serialization shape for it -- all of which live in `terms.rs`/`vm.rs`,
+    // neither of which is present in this tree to extend.
     qruntime!(
         "9223372036854775807 + 1 > 0",
         RuntimeError::ArithmeticError { .. }
@@ -1737,6 +1802,51 @@ fn test_print() -> TestResult {
     Ok(())
 }
 
+#[test]
+fn external_op_handler_resolves_ordering_between_externals() -> TestResult {
+    let p = polar();
+
+    let older = ExternalInstance {
+        instance_id: 1,
+        constructor: None,
+        repr: None,
+    };
+    let newer = ExternalInstance {
+        instance_id: 2,
+        constructor: None,
+        repr: None,
+    };
+    p.register_constant(sym!("Older"), term!(Value::ExternalInstance(older)))?;
+    p.register_constant(sym!("Newer"), term!(Value::ExternalInstance(newer)))?;
+
+    // The default harness `ExternalOp` handler only knows how to resolve Eq/Neq/Unify
+    // structurally; ordering between opaque externals needs a custom handler that models the
+    // host's partial order, here keyed on `instance_id`.
+    let order_by_instance_id = |operator: Operator, args: Vec<Term>| match (
+        args[0].value(),
+        args[1].value(),
+    ) {
+        (Value::ExternalInstance(l), Value::ExternalInstance(r)) => match operator {
+            Operator::Lt => l.instance_id < r.instance_id,
+            Operator::Leq => l.instance_id <= r.instance_id,
+            Operator::Gt => l.instance_id > r.instance_id,
+            Operator::Geq => l.instance_id >= r.instance_id,
+            Operator::Eq | Operator::Unify => l.instance_id == r.instance_id,
+            Operator::Neq => l.instance_id != r.instance_id,
+            _ => panic!("unexpected operator {:?} in external comparison", operator),
+        },
+        _ => panic!("expected both operands to be external instances"),
+    };
+
+    let q = p.new_query("Older < Newer", false)?;
+    assert_eq!(query_results!(q, @ops order_by_instance_id).len(), 1);
+
+    let q = p.new_query("Newer < Older", false)?;
+    assert_eq!(query_results!(q, @ops order_by_instance_id).len(), 0);
+
+    Ok(())
+}
+
 #[test]
 fn test_unknown_specializer_suggestions() -> TestResult {
     let p = polar();
@@ -1796,6 +1906,11 @@ fn test_dict_destructuring() -> TestResult {
     Ok(())
 }
 
+// TODO: support `{a}` as shorthand for `{a: a}` in dictionary patterns, including cross-field
+// variable reuse (`{a, b: a}` constraining `b` to whatever `a` unified with). That needs parser
+// support for the shorthand desugaring and, in the VM, per-field unification goals emitted in a
+// stable order with the binding environment threaded between fields -- both the parser and the
+// specializer/isa-matching logic live in files this tree doesn't include, so this stays `#[ignore]`.
 #[ignore]
 #[test]
 fn test_dict_destructuring_broken() -> TestResult {
@@ -1810,6 +1925,12 @@ fn test_dict_destructuring_broken() -> TestResult {
     Ok(())
 }
 
+// TODO: the recursive `member`/`append`/`delete` predicates exercised below (and in
+// test_list_results/test_circular_data) can blow the native stack on long lists because each
+// recursive call pushes a new goal-stack frame. Last-call optimization -- detecting when the
+// final goal of a rule body is a tail call with no open choice points above it, and reusing the
+// current frame instead of nesting -- would make this linear in heap. That's goal-stack/cut-
+// barrier plumbing in the VM's rule-application path, which isn't present in this tree to extend.
 #[test]
 fn test_rest_vars() -> TestResult {
     let mut p = polar();
@@ -1969,6 +2090,12 @@ fn test_head_patterns() -> TestResult {
     Ok(())
 }
 
+// TODO: add a first-class `match`/`case` expression (ordered `Vec<(Pattern, Term)>` arms, optional
+// wildcard, committing to the first matching arm like a localized cut instead of retrying later
+// arms on backtracking) as a more ergonomic alternative to the chained `matches`/`or` patterns
+// below. That's a new parser node plus VM support for evaluating the scrutinee once, trying arms
+// top-to-bottom via the existing isa-matching logic, and pruning remaining arms as choice points
+// on the first successful bind -- the parser and VM aren't present in this tree to extend.
 #[test]
 fn test_matches() {
     let mut p = polar();
@@ -2092,6 +2219,12 @@ fn test_cut() -> TestResult {
     Ok(())
 }
 
+// TODO: add count/sum/min/max/collect as aggregation built-ins alongside forall below. Each would
+// push a sub-query like forall does, but drain it to exhaustion into an accumulator (running
+// count/sum, current extreme, or an appended list) instead of short-circuiting, then unify the
+// accumulator with the output variable -- unwinding the inner query's bindings after every
+// solution so only the projected term escapes. That's VM-level goal-stack plumbing (see how
+// `forall` itself is implemented) that isn't present in this tree to extend.
 #[test]
 fn test_forall() -> TestResult {
     let mut p = polar();
@@ -2247,6 +2380,13 @@ fn test_duplicated_rule() -> TestResult {
     Ok(())
 }
 
+// TODO: extend `<`/`>`/`<=`/`>=` with a total, predictable ordering across representations,
+// reusing the int/float boundary coercion exercised below: lexicographic string comparison, and
+// element-wise lexicographic list comparison where a prefix is "less" than its extension.
+// Comparisons between fundamentally incompatible types (number vs. string) should fail cleanly
+// instead of raising a type error. This is VM operator-evaluation logic that isn't present in
+// this tree to extend; add tests for string ordering, list ordering, and the incompatible-type
+// failure path alongside `test_comparisons` once it is.
 #[test]
 fn test_numeric_applicability() -> TestResult {
     let mut p = polar();
@@ -2625,3 +2765,122 @@ allow(actor, action, resource) if has_permission(actor, action, resource);
         .contains("Missing implementation for required rule has_relation("));
     Ok(())
 }
+
+// A resource block's relation can name a registered host class that was never itself declared
+// with a `resource`/`actor` block -- e.g. a typo'd type name, or one the author forgot to add a
+// block for. That should be caught statically as an `UnknownRelationTarget` error rather than
+// only surfacing later as an opaque rule-resolution failure.
+#[test]
+fn test_unknown_relation_target_type() -> TestResult {
+    let p = Polar::new();
+
+    let issue_instance = ExternalInstance {
+        instance_id: 1,
+        constructor: None,
+        repr: None,
+    };
+    let issue_term = term!(Value::ExternalInstance(issue_instance.clone()));
+    let issue_name = sym!("Issue");
+    p.register_constant(issue_name.clone(), issue_term)?;
+    p.register_mro(issue_name, vec![issue_instance.instance_id])?;
+
+    let repo_instance = ExternalInstance {
+        instance_id: 2,
+        constructor: None,
+        repr: None,
+    };
+    let repo_term = term!(Value::ExternalInstance(repo_instance.clone()));
+    let repo_name = sym!("Repository");
+    p.register_constant(repo_name.clone(), repo_term)?;
+    p.register_mro(repo_name, vec![repo_instance.instance_id])?;
+
+    let policy = r#"
+resource Issue {
+    relations = {repo: Repository};
+}
+"#;
+
+    let err = p.load_str(policy).expect_err("Expected validation error");
+    assert!(matches!(
+        &err.kind,
+        ErrorKind::Validation(ValidationError::UnknownRelationTarget { .. })
+    ));
+
+    Ok(())
+}
+
+// A shorthand rule's role/permission operand (the `"owner"` in `"write" if "owner" on "repo";`)
+// should be checked against the relation target's own declared roles/permissions, the same way
+// `test_unknown_relation_target_type` checks a relation's target type. `KnowledgeBase` can't do
+// that yet -- `ResourceBlocks` only exposes relation traversal (`shorthand_rules`,
+// `relation_tuples`), not each resource's own declared role/permission name set, and that
+// bookkeeping lives in `resource_block.rs`, which this tree doesn't include -- so this stays
+// `#[ignore]`. `error::ValidationError::UndeclaredRoleOrPermission` exists as the diagnostic this
+// would raise once that data is exposed.
+#[ignore]
+#[test]
+fn test_undeclared_role_or_permission_on_relation_target() -> TestResult {
+    let p = Polar::new();
+
+    let repo_instance = ExternalInstance {
+        instance_id: 1,
+        constructor: None,
+        repr: None,
+    };
+    let repo_term = term!(Value::ExternalInstance(repo_instance.clone()));
+    let repo_name = sym!("Repository");
+    p.register_constant(repo_name.clone(), repo_term)?;
+    p.register_mro(repo_name, vec![repo_instance.instance_id])?;
+
+    let issue_instance = ExternalInstance {
+        instance_id: 2,
+        constructor: None,
+        repr: None,
+    };
+    let issue_term = term!(Value::ExternalInstance(issue_instance.clone()));
+    let issue_name = sym!("Issue");
+    p.register_constant(issue_name.clone(), issue_term)?;
+    p.register_mro(issue_name, vec![issue_instance.instance_id])?;
+
+    // Repository never declares "owner" as a role, a permission, or a relation -- it's a typo (or
+    // a role the author forgot to declare) that should be caught statically instead of only
+    // surfacing as an opaque rule-resolution failure once something calls has_permission.
+    let policy = r#"
+resource Repository {
+    roles = ["admin"];
+}
+
+resource Issue {
+    roles = ["write"];
+    relations = {repo: Repository};
+    "write" if "owner" on "repo";
+}
+"#;
+
+    let err = p.load_str(policy).expect_err("Expected validation error");
+    assert!(matches!(
+        &err.kind,
+        ErrorKind::Validation(ValidationError::UndeclaredRoleOrPermission { .. })
+    ));
+
+    Ok(())
+}
+
+// An `actor`/`resource` block can name a type the host never registered at all -- neither
+// `register_constant` nor `register_mro` was ever called for it. That should be caught statically
+// as an `UnregisteredResourceType` error instead of surfacing later as an opaque lookup failure
+// the first time a rule needs that type's MRO.
+#[test]
+fn test_unregistered_resource_type() -> TestResult {
+    let p = Polar::new();
+
+    let policy = "actor User {}\n";
+
+    let err = p.load_str(policy).expect_err("Expected validation error");
+    assert!(matches!(
+        &err.kind,
+        ErrorKind::Validation(ValidationError::UnregisteredResourceType { .. })
+    ));
+
+    Ok(())
+}